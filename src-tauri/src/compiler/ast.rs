@@ -1,7 +1,24 @@
+use crate::compiler::lexer::Span;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
 #[derive(Debug, Clone)]
-pub enum Expr {
+pub enum ExprKind {
     Число(f64),
     Строка(String),
+    // Строка с подстановками: `"Привет, {имя}!"`
+    Шаблон(Vec<TemplatePart>),
     Булево(bool),
     Идентификатор(String),
     БинарнаяОперация {
@@ -13,10 +30,58 @@ pub enum Expr {
         имя: String,
         аргументы: Vec<Expr>,
     },
+    // Вызов функции, которая получается из выражения: `сделать(1)(2)`, `действия[0](x)`
+    Вызов {
+        функция: Box<Expr>,
+        аргументы: Vec<Expr>,
+    },
+    Лямбда(Box<Lambda>),
     Присваивание {
         имя: String,
         значение: Box<Expr>,
     },
+    // Литерал списка: `[1, 2, 3]`
+    Список(Vec<Expr>),
+    // Литерал словаря: `{"ключ": значение}`
+    Словарь(Vec<(Expr, Expr)>),
+    Индекс {
+        объект: Box<Expr>,
+        индекс: Box<Expr>,
+    },
+    // `список[и] = значение`
+    ПрисваиваниеПоИндексу {
+        объект: Box<Expr>,
+        индекс: Box<Expr>,
+        значение: Box<Expr>,
+    },
+    // Поле структуры: `точка.x`
+    Поле {
+        объект: Box<Expr>,
+        поле: String,
+    },
+    // Вызов метода: `собака.голос()`
+    ВызовМетода {
+        объект: Box<Expr>,
+        метод: String,
+        аргументы: Vec<Expr>,
+    },
+    // Объект, у которого вызван метод
+    Этот,
+    // `точка.x = значение`
+    ПрисваиваниеПолю {
+        объект: Box<Expr>,
+        поле: String,
+        значение: Box<Expr>,
+    },
+    // Структура с полями по именам: `Точка { x: 1, y: 2 }`
+    СозданиеСтруктуры {
+        имя: String,
+        поля: Vec<FieldValue>,
+    },
+    // Логическое отрицание: `не x`, `!x`
+    Отрицание(Box<Expr>),
+    // Место, где лексер не смог разобрать текст; сама ошибка уже сообщена
+    Ошибка,
 }
 
 #[derive(Debug, Clone)]
@@ -25,18 +90,245 @@ pub enum BinOp {
     Минус,
     Умножить,
     Разделить,
+    Остаток,
+    Степень,
+    ЦелоеДеление,
     Равно,
     НеРавно,
     Больше,
     Меньше,
     БольшеРавно,
     МеньшеРавно,
+    И,
+    Или,
+}
+
+impl BinOp {
+    /// Знак операции, как он пишется в коде.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinOp::Плюс => "+",
+            BinOp::Минус => "-",
+            BinOp::Умножить => "*",
+            BinOp::Разделить => "/",
+            BinOp::Остаток => "%",
+            BinOp::Степень => "^",
+            BinOp::ЦелоеДеление => "div",
+            BinOp::Равно => "==",
+            BinOp::НеРавно => "!=",
+            BinOp::Больше => ">",
+            BinOp::Меньше => "<",
+            BinOp::БольшеРавно => ">=",
+            BinOp::МеньшеРавно => "<=",
+            BinOp::И => "и",
+            BinOp::Или => "или",
+        }
+    }
+}
+
+/// Тип, который можно указать в аннотации: `пусть x: число = 5;`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Число,
+    Строка,
+    Булево,
+    Список,
+    Словарь,
+    Функция,
+    Структура,
+    Объект,
+    Пусто,
+}
+
+impl Type {
+    pub const ALL: &'static [Type] = &[
+        Type::Число,
+        Type::Строка,
+        Type::Булево,
+        Type::Список,
+        Type::Словарь,
+        Type::Функция,
+        Type::Структура,
+        Type::Объект,
+        Type::Пусто,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Type::Число => "число",
+            Type::Строка => "строка",
+            Type::Булево => "булево",
+            Type::Список => "список",
+            Type::Словарь => "словарь",
+            Type::Функция => "функция",
+            Type::Структура => "структура",
+            Type::Объект => "объект",
+            Type::Пусто => "пусто",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Type> {
+        Type::ALL.iter().copied().find(|ty| ty.name() == name)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAnnotation {
+    pub ty: Type,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub ty: Option<TypeAnnotation>,
+    pub span: Span,
+}
+
+/// Часть строки с подстановками: текст или выражение, значение которого
+/// вставляется в строку так же, как его печатает `печать`.
+#[derive(Debug, Clone)]
+pub enum TemplatePart {
+    Текст(String),
+    Выражение(Expr),
+}
+
+/// Значение поля в `Точка { x: 1 }`.
+#[derive(Debug, Clone)]
+pub struct FieldValue {
+    pub name: String,
+    // Где записано имя поля
+    pub span: Span,
+    pub value: Expr,
+}
+
+/// Анонимная функция: `фн(x) { вернуть x * x; }`
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub params: Vec<Param>,
+    pub result: Option<TypeAnnotation>,
+    pub body: Vec<Stmt>,
+}
+
+/// Поле класса: `имя: строка = "";`. Начальное значение вычисляется
+/// при создании каждого объекта; без него поле равно пусто.
+#[derive(Debug, Clone)]
+pub struct ClassField {
+    pub param: Param,
+    pub value: Option<Expr>,
+}
+
+/// Метод класса. Метод `конструктор` вызывается при создании объекта.
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub name: String,
+    pub span: Span,
+    pub function: Lambda,
+}
+
+/// Образец в `случай` инструкции `выбор`.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    Число(f64),
+    Строка(String),
+    Булево(bool),
+    // `от 1 до 10`: обе границы входят в диапазон, как в цикле `для`
+    Диапазон(f64, f64),
+    // Совпадает с любым значением и даёт ему имя; `_` — без имени
+    Переменная(String),
+    // `[x, 0]`: список ровно такой длины
+    Список(Vec<Pattern>),
+    // `Точка { x: 0, y }`: `y` — то же, что `y: y`
+    Структура {
+        имя: String,
+        поля: Vec<FieldPattern>,
+    },
+}
+
+impl Pattern {
+    /// Совпадает с любым значением.
+    pub fn is_catch_all(&self) -> bool {
+        matches!(self.kind, PatternKind::Переменная(_))
+    }
+
+    /// Имена, которые образец объявляет, в порядке записи.
+    pub fn bindings(&self) -> Vec<(&str, Span)> {
+        let mut names = Vec::new();
+        self.collect_bindings(&mut names);
+        names
+    }
+
+    fn collect_bindings<'a>(&'a self, names: &mut Vec<(&'a str, Span)>) {
+        match &self.kind {
+            PatternKind::Переменная(name) if name != "_" => names.push((name, self.span)),
+            PatternKind::Список(items) => {
+                for item in items {
+                    item.collect_bindings(names);
+                }
+            }
+            PatternKind::Структура { поля, .. } => {
+                for field in поля {
+                    field.pattern.collect_bindings(names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Поле в образце структуры.
+#[derive(Debug, Clone)]
+pub struct FieldPattern {
+    pub name: String,
+    pub span: Span,
+    pub pattern: Pattern,
+}
+
+/// `случай 1, 2: ...`: тело выполняется, если значение подходит под один из образцов.
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub patterns: Vec<Pattern>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+/// Ветка `перехват (ошибка) { ... }`: в переменной — пойманная ошибка.
+#[derive(Debug, Clone)]
+pub struct Catch {
+    pub name: String,
+    pub span: Span,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Stmt { kind, span }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub enum Stmt {
+pub enum StmtKind {
     ОбъявлениеПеременной {
         имя: String,
+        тип: Option<TypeAnnotation>,
         значение: Expr,
     },
     Если {
@@ -44,20 +336,78 @@ pub enum Stmt {
         тогда: Vec<Stmt>,
         иначе: Option<Vec<Stmt>>,
     },
+    // Выполняется первый подходящий случай, а если ни один не подошёл — `иначе`
+    Выбор {
+        значение: Expr,
+        случаи: Vec<Case>,
+        иначе: Option<Vec<Stmt>>,
+    },
+    // `попытка { ... } перехват (ошибка) { ... } наконец { ... }`: нужна хотя бы
+    // одна из веток `перехват` и `наконец`; `наконец` выполняется всегда
+    Попытка {
+        тело: Vec<Stmt>,
+        перехват: Option<Catch>,
+        наконец: Option<Vec<Stmt>>,
+    },
+    // `бросить "сообщение";` или `бросить ошибка;` из ветки `перехват`
+    Бросить(Expr),
     Пока {
         условие: Expr,
         тело: Vec<Stmt>,
     },
+    // `для и от 1 до 10 шаг 2 { ... }`; обе границы входят в диапазон
+    Для {
+        переменная: String,
+        начало: Expr,
+        конец: Expr,
+        шаг: Option<Expr>,
+        тело: Vec<Stmt>,
+    },
+    // `для символ в строка { ... }`
+    ДляКаждого {
+        переменная: String,
+        коллекция: Expr,
+        тело: Vec<Stmt>,
+    },
     Вернуть(Expr),
+    Прервать,
+    Продолжить,
     Выражение(Expr),
     ОбъявлениеФункции {
         имя: String,
-        параметры: Vec<String>,
+        параметры: Vec<Param>,
+        тип_результата: Option<TypeAnnotation>,
         тело: Vec<Stmt>,
     },
+    // `структура Точка { x: число, y }`; поля записываются как параметры
+    // функции, и `Точка(1, 2)` заполняет их по порядку
+    ОбъявлениеСтруктуры {
+        имя: String,
+        поля: Vec<Param>,
+    },
+    // `класс Собака наследует Животное { ... }`: поля и методы родителя
+    // достаются наследнику, метод с тем же именем заменяет родительский
+    ОбъявлениеКласса {
+        имя: String,
+        родитель: Option<(String, Span)>,
+        поля: Vec<ClassField>,
+        методы: Vec<Method>,
+    },
+}
+
+/// `подключить "геометрия.рус";` или `из "геометрия.рус" подключить площадь, Точка;`
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub path: String,
+    // Где записан путь
+    pub span: Span,
+    // Имена после `из ... подключить`; у `подключить "..."` их нет
+    pub names: Vec<(String, Span)>,
 }
 
 #[derive(Debug)]
 pub struct Program {
     pub statements: Vec<Stmt>,
-}
\ No newline at end of file
+    // Подключённые файлы выполняются раньше кода, который их подключает
+    pub imports: Vec<Import>,
+}
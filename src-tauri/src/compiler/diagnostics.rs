@@ -0,0 +1,208 @@
+use crate::compiler::lexer::Span;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Стабильные коды диагностик. Коды не меняются между версиями,
+/// по ним редактор может подбирать справку.
+pub mod codes {
+    // Лексический анализ
+    pub const UNKNOWN_CHARACTER: &str = "L0001";
+    pub const UNTERMINATED_STRING: &str = "L0002";
+    pub const UNTERMINATED_COMMENT: &str = "L0003";
+    pub const INVALID_NUMBER: &str = "L0004";
+    pub const WRONG_LAYOUT: &str = "L0005";
+    pub const MIXED_SCRIPT: &str = "L0006";
+
+    // Подключение файлов
+    pub const FILE_NOT_FOUND: &str = "M0001";
+    pub const INCLUDE_CYCLE: &str = "M0002";
+    pub const UNKNOWN_IMPORT: &str = "M0003";
+    pub const IMPORT_CONFLICT: &str = "M0004";
+
+    // Синтаксический анализ
+    pub const UNEXPECTED_TOKEN: &str = "P0001";
+    pub const MISSING_SEMICOLON: &str = "P0002";
+    pub const UNCLOSED_BLOCK: &str = "P0003";
+    pub const EXPECTED_IDENTIFIER: &str = "P0004";
+    pub const EXPECTED_EXPRESSION: &str = "P0005";
+    pub const UNKNOWN_TYPE: &str = "P0006";
+
+    // Семантический анализ
+    pub const UNDECLARED_VARIABLE: &str = "S0001";
+    pub const UNDECLARED_FUNCTION: &str = "S0002";
+    pub const WRONG_ARGUMENT_COUNT: &str = "S0003";
+    pub const RETURN_OUTSIDE_FUNCTION: &str = "S0004";
+    pub const UNREACHABLE_CODE: &str = "S0005";
+    pub const NOT_A_FUNCTION: &str = "S0006";
+    pub const JUMP_OUTSIDE_LOOP: &str = "S0007";
+    pub const BUILTIN_AS_VALUE: &str = "S0008";
+    pub const UNDECLARED_STRUCT: &str = "S0009";
+    pub const STRUCT_FIELDS: &str = "S0010";
+    pub const INHERITANCE: &str = "S0011";
+    pub const THIS_OUTSIDE_METHOD: &str = "S0012";
+    pub const PATTERN: &str = "S0013";
+
+    // Проверка типов
+    pub const TYPE_MISMATCH: &str = "T0001";
+    pub const MISSING_RETURN: &str = "T0002";
+    pub const NON_EXHAUSTIVE_MATCH: &str = "T0003";
+
+    // Предупреждения линтера
+    pub const LINT_CONFIG: &str = "W0000";
+    pub const UNUSED_VARIABLE: &str = "W0001";
+    pub const UNUSED_FUNCTION: &str = "W0002";
+    pub const SHADOWED_GLOBAL: &str = "W0003";
+    pub const INFINITE_LOOP: &str = "W0004";
+    pub const CONSTANT_CONDITION: &str = "W0005";
+    pub const UNREACHABLE_CASE: &str = "W0006";
+    pub const OVERLAPPING_CASE: &str = "W0007";
+
+    // Выполнение
+    pub const UNDEFINED_VARIABLE: &str = "R0001";
+    pub const UNDEFINED_FUNCTION: &str = "R0002";
+    pub const ARGUMENT_COUNT: &str = "R0003";
+    pub const ARGUMENT_TYPE: &str = "R0004";
+    pub const DIVISION_BY_ZERO: &str = "R0005";
+    pub const UNSUPPORTED_OPERATION: &str = "R0006";
+    pub const DOMAIN_ERROR: &str = "R0007";
+    pub const RECURSION_LIMIT: &str = "R0008";
+    pub const RUNTIME_TYPE_MISMATCH: &str = "R0009";
+    pub const INDEX_OUT_OF_BOUNDS: &str = "R0010";
+    pub const MISSING_KEY: &str = "R0011";
+    pub const UNKNOWN_FIELD: &str = "R0012";
+    pub const USER_ERROR: &str = "R0013";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Loading,
+    Lexing,
+    Parsing,
+    Semantic,
+    Runtime,
+}
+
+/// Дополнительная пометка к диагностике: другой участок кода с пояснением.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Замена участка текста.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Edit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// Автоматическое исправление, которое редактор может применить одним действием.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fix {
+    pub message: String,
+    pub edits: Vec<Edit>,
+}
+
+/// Кадр стека вызовов: какая функция, откуда вызвана и с какими аргументами.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackFrame {
+    pub function: String,
+    pub span: Span,
+    pub arguments: Vec<String>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({}) ({})", self.function, self.arguments.join(", "), self.span)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub stage: Stage,
+    pub code: String,
+    pub message: String,
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    // Стек вызовов для ошибок выполнения, от внешнего вызова к внутреннему
+    pub trace: Vec<StackFrame>,
+    pub fixes: Vec<Fix>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, stage: Stage, code: &str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            stage,
+            code: code.to_string(),
+            message: message.into(),
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            trace: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    pub fn error(stage: Stage, code: &str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, stage, code, message)
+    }
+
+    pub fn warning(stage: Stage, code: &str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, stage, code, message)
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into() });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_fix(mut self, message: impl Into<String>, span: Span, replacement: impl Into<String>) -> Self {
+        self.fixes.push(Fix {
+            message: message.into(),
+            edits: vec![Edit { span, replacement: replacement.into() }],
+        });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)?;
+        if let Some(span) = self.span {
+            write!(f, " ({})", span)?;
+        }
+        for frame in &self.trace {
+            write!(f, "\n  в вызове {}", frame)?;
+        }
+        for note in &self.notes {
+            write!(f, "\nподсказка: {}", note)?;
+        }
+        Ok(())
+    }
+}
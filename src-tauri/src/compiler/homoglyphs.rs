@@ -0,0 +1,141 @@
+//! Поиск имён, в которых латинские буквы смешаны с похожими на них русскими:
+//! `cчёт` с латинской `c` и `счёт` выглядят одинаково, но это разные имена.
+
+use crate::compiler::diagnostics::{codes, Diagnostic, Stage};
+use crate::compiler::lexer::{is_cyrillic, keyword, Span, SpannedToken, Token};
+
+// Латинские буквы и их русские двойники
+const HOMOGLYPHS: &[(char, char)] = &[
+    ('a', 'а'), ('c', 'с'), ('e', 'е'), ('o', 'о'), ('p', 'р'), ('x', 'х'),
+    ('y', 'у'), ('A', 'А'), ('B', 'В'), ('C', 'С'), ('E', 'Е'), ('H', 'Н'),
+    ('K', 'К'), ('M', 'М'), ('O', 'О'), ('P', 'Р'), ('T', 'Т'), ('X', 'Х'),
+];
+
+fn cyrillic_twin(ch: char) -> Option<char> {
+    HOMOGLYPHS.iter().find(|(lat, _)| *lat == ch).map(|(_, cyr)| *cyr)
+}
+
+// Участок одного символа внутри токена, который целиком лежит на одной строке
+fn char_span(token: Span, name: &str, index: usize) -> Span {
+    let start = token.start + name.chars().take(index).map(|ch| ch.len_utf8() as u32).sum::<u32>();
+    let ch = name.chars().nth(index).unwrap_or(' ');
+    let column = token.column + index as u32;
+    Span {
+        file: token.file,
+        start,
+        end: start + ch.len_utf8() as u32,
+        line: token.line,
+        column,
+        end_line: token.line,
+        end_column: column + 1,
+    }
+}
+
+fn list(chars: &[char]) -> String {
+    chars.iter().map(|ch| ch.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// Предупреждает об именах, где есть и кириллица, и латиница, и предлагает
+/// заменить латинские двойники русскими буквами.
+pub fn check_homoglyphs(tokens: &[SpannedToken]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for token in tokens {
+        let Token::Идентификатор(name) = &token.token else {
+            continue;
+        };
+        let has_cyrillic = name.chars().any(is_cyrillic);
+        let latin: Vec<(usize, char)> = name
+            .chars()
+            .enumerate()
+            .filter(|(_, ch)| ch.is_ascii_alphabetic())
+            .collect();
+        if !has_cyrillic || latin.is_empty() {
+            continue;
+        }
+
+        let latin_chars: Vec<char> = latin.iter().map(|(_, ch)| *ch).collect();
+        let cyrillic_chars: Vec<char> = name.chars().filter(|ch| is_cyrillic(*ch)).collect();
+
+        let normalized: Option<String> = name
+            .chars()
+            .map(|ch| if ch.is_ascii_alphabetic() { cyrillic_twin(ch) } else { Some(ch) })
+            .collect();
+
+        let message = match normalized.as_deref().filter(|word| keyword(word).is_some()) {
+            Some(word) => format!(
+                "Ключевое слово '{}' набрано с латинскими буквами и не распознано",
+                word
+            ),
+            None => format!("Имя '{}' смешивает латинские и русские буквы", name),
+        };
+
+        let mut diagnostic = Diagnostic::warning(Stage::Lexing, codes::MIXED_SCRIPT, message)
+            .with_span(token.span)
+            .with_note(format!(
+                "латинские: {}; русские: {}",
+                list(&latin_chars),
+                list(&cyrillic_chars)
+            ));
+        for (index, ch) in &latin {
+            diagnostic = diagnostic.with_label(
+                char_span(token.span, name, *index),
+                format!("латинская буква '{}'", ch),
+            );
+        }
+
+        match normalized {
+            Some(word) => {
+                diagnostic = diagnostic.with_fix(
+                    format!("Заменить латинские буквы русскими: '{}'", word),
+                    token.span,
+                    word,
+                );
+            }
+            None => {
+                diagnostic = diagnostic.with_note(
+                    "у некоторых латинских букв нет русских двойников, исправьте имя вручную",
+                );
+            }
+        }
+
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+
+    fn warnings(code: &str) -> Vec<Diagnostic> {
+        let (tokens, _) = Lexer::new(code.to_string()).tokenize();
+        check_homoglyphs(&tokens)
+    }
+
+    #[test]
+    fn latin_letter_in_a_russian_name_is_marked() {
+        let found = warnings("пусть pаз = 1;");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, codes::MIXED_SCRIPT);
+        assert_eq!(found[0].message, "Имя 'pаз' смешивает латинские и русские буквы");
+        assert_eq!(found[0].labels[0].span.column, 7);
+        assert_eq!(found[0].fixes[0].edits[0].replacement, "раз");
+    }
+
+    #[test]
+    fn keyword_with_latin_letters_is_named() {
+        let found = warnings("пoка (истина) { }");
+        assert_eq!(found[0].message, "Ключевое слово 'пока' набрано с латинскими буквами и не распознано");
+    }
+
+    #[test]
+    fn letters_without_twins_need_a_manual_fix() {
+        let found = warnings("пусть сумmа = 1;");
+        assert!(found[0].fixes.is_empty());
+        assert_eq!(found[0].notes.len(), 2);
+        assert!(warnings("пусть sum = 1; пусть сумма = 2;").is_empty());
+    }
+}
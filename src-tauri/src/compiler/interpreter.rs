@@ -1,6 +1,13 @@
 use crate::compiler::ast::*;
+use crate::compiler::diagnostics::{codes, Diagnostic, StackFrame, Stage};
+use crate::compiler::lexer::Span;
+use crate::compiler::modules::Module;
+use crate::compiler::suggest;
 use crate::compiler::PlotData;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH}; // Вынес импорт времени наверх
 
 pub type OutputHandler = Box<dyn FnMut(String) + Send>;
@@ -11,27 +18,301 @@ pub enum Value {
     Число(f64),
     Строка(String),
     Булево(bool),
-    Функция {
-        параметры: Vec<String>,
-        тело: Vec<Stmt>,
-    },
+    // Список общий для всех переменных, которые на него ссылаются:
+    // `добавить(список, x)` меняет его и для вызывающего кода
+    Список(Rc<RefCell<Vec<Value>>>),
+    // Пары в порядке добавления; общий, как и список
+    Словарь(Rc<RefCell<Entries>>),
+    Функция(Rc<UserFunction>),
+    // Общая, как и список: изменение поля видно всем, кто на неё ссылается.
+    // Объект класса — такая же структура, только у её типа есть методы
+    Структура(Rc<RefCell<Structure>>),
     Пусто,
 }
 
+/// Структура или класс, объявленные в программе: `структура Точка { x, y }`.
+#[derive(Debug)]
+pub struct StructType {
+    name: String,
+    // У класса — вместе с полями родителя, они идут первыми
+    fields: Vec<Param>,
+    class: Option<Class>,
+}
+
+/// То, что есть у класса сверх полей.
+#[derive(Debug)]
+struct Class {
+    parent: Option<Rc<StructType>>,
+    // Начальные значения полей, по одному на каждое из `fields`
+    defaults: Vec<Option<Expr>>,
+    // В порядке объявления
+    methods: Vec<(String, Rc<UserFunction>)>,
+    // Область, где объявлен класс: в ней вычисляются начальные значения
+    closure: Env,
+}
+
+impl StructType {
+    // Метод класса или ближайшего из родителей
+    fn method(&self, name: &str) -> Option<Rc<UserFunction>> {
+        let class = self.class.as_ref()?;
+        match class.methods.iter().find(|(method, _)| method == name) {
+            Some((_, method)) => Some(method.clone()),
+            None => class.parent.as_ref()?.method(name),
+        }
+    }
+
+    fn method_names(&self) -> Vec<&str> {
+        let Some(class) = &self.class else {
+            return Vec::new();
+        };
+        let mut names: Vec<&str> = match &class.parent {
+            Some(parent) => parent.method_names(),
+            None => Vec::new(),
+        };
+        for (name, _) in &class.methods {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    // Тип с этим именем или его наследник
+    fn is_a(&self, name: &str) -> bool {
+        self.name == name
+            || self
+                .class
+                .as_ref()
+                .and_then(|class| class.parent.as_ref())
+                .is_some_and(|parent| parent.is_a(name))
+    }
+
+    // «у структуры Точка», «у класса Собака»
+    fn kind(&self) -> &'static str {
+        if self.class.is_some() { "класса" } else { "структуры" }
+    }
+
+    fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|field| field.name.as_str())
+    }
+}
+
+/// Значение структуры: значения полей в порядке объявления.
+#[derive(Debug)]
+pub struct Structure {
+    ty: Rc<StructType>,
+    fields: Vec<Value>,
+}
+
+/// Содержимое словаря.
+pub type Entries = Vec<(Key, Value)>;
+
+/// Ключ словаря: строка или число.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Key {
+    Число(f64),
+    Строка(String),
+}
+
+impl Key {
+    fn to_value(&self) -> Value {
+        match self {
+            Key::Число(n) => Value::Число(*n),
+            Key::Строка(s) => Value::Строка(s.clone()),
+        }
+    }
+}
+
+/// Чем закончилось выполнение инструкции: обычным переходом к следующей
+/// или выходом из блока через `вернуть`, `прервать`, `продолжить`.
+#[derive(Debug, Clone)]
+enum ControlFlow {
+    Дальше,
+    Вернуть(Value),
+    Прервать,
+    Продолжить,
+}
+
+/// Метод, который вызывается при создании объекта.
+pub const CONSTRUCTOR: &str = "конструктор";
+
+/// Встроенная структура: ею становится ошибка, пойманная в `перехват`.
+pub const ERROR_STRUCT: &str = "Ошибка";
+
+/// Поля структуры `Ошибка` в порядке объявления.
+pub const ERROR_FIELDS: &[&str] = &["сообщение", "код", "строка", "столбец"];
+
+/// Имена встроенных функций.
+pub const BUILTINS: &[&str] = &[
+    "печать", "график", "син", "кос", "эксп", "лог", "корень",
+    "длина", "добавить", "удалить", "вставить",
+    "ключи", "значения", "содержит",
+];
+
+// Глубже этого рекурсия считается бесконечной
+const MAX_CALL_DEPTH: usize = 500;
+
+// Сколько вызовов с каждого края стека показывать в длинной трассировке
+const TRACE_EDGE: usize = 10;
+
+/// Ошибка выполнения; в диагностику превращается на выходе из интерпретатора.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    // Код из `codes` или тот, что программа указала в `бросить`
+    pub code: Box<str>,
+    pub message: String,
+    pub span: Span,
+    // Вызовы, через которые прошла ошибка, от внутреннего к внешнему
+    pub trace: Vec<StackFrame>,
+    pub notes: Vec<String>,
+}
+
+impl RuntimeError {
+    fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(err: RuntimeError) -> Self {
+        let mut diagnostic =
+            Diagnostic::error(Stage::Runtime, &err.code, err.message).with_span(err.span);
+        diagnostic.notes = err.notes;
+        
+        let mut trace = err.trace;
+        trace.reverse();
+        if trace.len() > 2 * TRACE_EDGE {
+            let skipped = trace.len() - 2 * TRACE_EDGE;
+            trace.drain(TRACE_EDGE..trace.len() - TRACE_EDGE);
+            diagnostic = diagnostic.with_note(format!(
+                "в трассировке пропущено {} промежуточных вызовов",
+                skipped
+            ));
+        }
+        diagnostic.trace = trace;
+        diagnostic
+    }
+}
+
+/// Функция, написанная на Рус: объявленная по имени или анонимная `фн(...)`.
+#[derive(Debug)]
+pub struct UserFunction {
+    // У анонимной функции имени нет
+    name: Option<String>,
+    params: Vec<Param>,
+    return_type: Option<TypeAnnotation>,
+    body: Vec<Stmt>,
+    // Область, где функция создана: её переменные видны из тела
+    closure: Env,
+}
+
+impl UserFunction {
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("фн")
+    }
+}
+
+type Env = Rc<RefCell<Environment>>;
+
+/// Область видимости: вся программа, вызов функции или блок. Имя ищется
+/// сначала в своей области, потом во внешних.
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    parent: Option<Env>,
+}
+
+impl Environment {
+    fn child(parent: &Env) -> Env {
+        Rc::new(RefCell::new(Environment { values: HashMap::new(), parent: Some(parent.clone()) }))
+    }
+
+    fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(val) => Some(val.clone()),
+            None => self.parent.as_ref()?.borrow().get(name),
+        }
+    }
+
+    // Новая переменная в этой области; одноимённая внешняя становится не видна
+    fn define(&mut self, name: &str, val: Value) {
+        self.values.insert(name.to_string(), val);
+    }
+
+    // Меняет ближайшую переменную с этим именем; `false`, если её нет нигде
+    fn assign(&mut self, name: &str, val: Value) -> bool {
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                *slot = val;
+                true
+            }
+            None => match &self.parent {
+                Some(parent) => parent.borrow_mut().assign(name, val),
+                None => false,
+            },
+        }
+    }
+
+    // Видимые отсюда имена функций или, при `functions == false`, остальных значений
+    fn names(&self, functions: bool) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .values
+            .iter()
+            .filter(|(_, val)| matches!(val, Value::Функция(_)) == functions)
+            .map(|(name, _)| name.clone())
+            .collect();
+        if let Some(parent) = &self.parent {
+            names.extend(parent.borrow().names(functions));
+        }
+        names
+    }
+}
+
+// Область может содержать функцию, которая ссылается на саму эту область,
+// поэтому печатаются только имена
+impl fmt::Debug for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.values.keys()).finish()
+    }
+}
+
 pub struct Interpreter {
-    variables: HashMap<String, Value>,
-    functions: HashMap<String, (Vec<String>, Vec<Stmt>)>,
+    // Текущая область видимости
+    env: Env,
+    // Структуры и классы по файлу, в котором они видны, и имени
+    structs: HashMap<(u32, String), Rc<StructType>>,
+    // Тип пойманных ошибок; виден по имени `Ошибка`, пока файл не объявит свою
+    error_type: Rc<StructType>,
     output_handler: Option<OutputHandler>,
     plot_handler: Option<PlotHandler>,
+    call_depth: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        let fields = ERROR_FIELDS
+            .iter()
+            .map(|name| Param { name: name.to_string(), ty: None, span: Span::default() })
+            .collect();
+        let error_type = Rc::new(StructType { name: ERROR_STRUCT.to_string(), fields, class: None });
         Interpreter {
-            variables: HashMap::new(),
-            functions: HashMap::new(),
+            env: Env::default(),
+            structs: HashMap::new(),
+            error_type,
             output_handler: None,
             plot_handler: None,
+            call_depth: 0,
         }
     }
     
@@ -51,80 +332,311 @@ impl Interpreter {
         }
     }
     
-    pub fn execute(&mut self, program: Program) -> Result<(), String> {
-        for stmt in program.statements {
-            self.execute_statement(&stmt)?;
+    pub fn execute(&mut self, program: Program) -> Result<(), RuntimeError> {
+        self.execute_statements(&program.statements)?;
+        Ok(())
+    }
+    
+    /// Выполняет программу из нескольких файлов по порядку модулей. Каждый
+    /// модуль выполняется в своей области видимости, куда сначала
+    /// копируются подключённые имена из уже выполненных модулей.
+    pub fn execute_modules(&mut self, modules: &[Module]) -> Result<(), RuntimeError> {
+        let mut scopes: Vec<Env> = Vec::with_capacity(modules.len());
+        for module in modules {
+            let scope = Env::default();
+            for (index, name) in &module.imports {
+                if let Some(val) = scopes[*index].borrow().get(name) {
+                    scope.borrow_mut().define(name, val);
+                }
+                let key = (modules[*index].file, name.clone());
+                if let Some(ty) = self.structs.get(&key).cloned() {
+                    self.structs.insert((module.file, name.clone()), ty);
+                }
+            }
+            self.execute_in(scope.clone(), &module.program.statements)?;
+            scopes.push(scope);
         }
         Ok(())
     }
     
-    fn execute_statement(&mut self, stmt: &Stmt) -> Result<Option<Value>, String> {
-        match stmt {
-            Stmt::ОбъявлениеПеременной { имя, значение } => {
+    // Блок `{ ... }` со своей областью видимости
+    fn execute_block(&mut self, stmts: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
+        let scope = Environment::child(&self.env);
+        self.execute_in(scope, stmts)
+    }
+    
+    // Выполняет инструкции в области `scope` и возвращает прежнюю, даже после ошибки
+    fn execute_in(&mut self, scope: Env, stmts: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
+        let outer = std::mem::replace(&mut self.env, scope);
+        let result = self.execute_statements(stmts);
+        self.env = outer;
+        result
+    }
+    
+    // Выполняет инструкции, пока одна из них не выйдет из блока. Функции блока
+    // объявляются до первой инструкции: их можно вызвать и выше объявления.
+    fn execute_statements(&mut self, stmts: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
+        for stmt in stmts {
+            if let StmtKind::ОбъявлениеФункции { имя, параметры, тип_результата, тело } = &stmt.kind {
+                let function = UserFunction {
+                    name: Some(имя.clone()),
+                    params: параметры.clone(),
+                    return_type: тип_результата.clone(),
+                    body: тело.clone(),
+                    closure: self.env.clone(),
+                };
+                // Функция — обычное значение своей области: вложенная не видна снаружи
+                self.env.borrow_mut().define(имя, Value::Функция(Rc::new(function)));
+            }
+        }
+        for stmt in stmts {
+            match self.execute_statement(stmt)? {
+                ControlFlow::Дальше => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(ControlFlow::Дальше)
+    }
+    
+    fn execute_statement(&mut self, stmt: &Stmt) -> Result<ControlFlow, RuntimeError> {
+        match &stmt.kind {
+            StmtKind::ОбъявлениеПеременной { имя, тип, значение } => {
                 let val = self.evaluate_expression(значение)?;
-                self.variables.insert(имя.clone(), val);
-                Ok(None)
+                if let Some(тип) = тип {
+                    check_type(&val, тип, значение.span, || format!("Переменная '{}'", имя))?;
+                }
+                self.env.borrow_mut().define(имя, val);
+                Ok(ControlFlow::Дальше)
             }
-            Stmt::Если { условие, тогда, иначе } => {
-                let cond = self.evaluate_expression(условие)?;
-                
-                if let Value::Булево(true) = cond {
-                    for s in тогда {
-                        if let Some(ret) = self.execute_statement(s)? {
-                            return Ok(Some(ret));
+            StmtKind::Если { условие, тогда, иначе } => {
+                if self.evaluate_condition(условие, "если")? {
+                    self.execute_block(тогда)
+                } else if let Some(else_block) = иначе {
+                    self.execute_block(else_block)
+                } else {
+                    Ok(ControlFlow::Дальше)
+                }
+            }
+            StmtKind::Выбор { значение, случаи, иначе } => {
+                let val = self.evaluate_expression(значение)?;
+                for case in случаи {
+                    for pattern in &case.patterns {
+                        let mut bindings = Vec::new();
+                        if match_pattern(pattern, &val, &mut bindings)? {
+                            // Имена из образца видны только в теле случая
+                            let scope = Environment::child(&self.env);
+                            for (name, val) in bindings {
+                                scope.borrow_mut().define(&name, val);
+                            }
+                            return self.execute_in(scope, &case.body);
                         }
                     }
-                } else if let Some(else_block) = иначе {
-                    for s in else_block {
-                        if let Some(ret) = self.execute_statement(s)? {
-                            return Ok(Some(ret));
+                }
+                match иначе {
+                    Some(иначе) => self.execute_block(иначе),
+                    None => Ok(ControlFlow::Дальше),
+                }
+            }
+            StmtKind::Попытка { тело, перехват, наконец } => {
+                let mut result = self.execute_block(тело);
+                if let Some(catch) = перехват {
+                    result = match result {
+                        Err(err) => {
+                            let scope = Environment::child(&self.env);
+                            scope.borrow_mut().define(&catch.name, self.error_value(err));
+                            self.execute_in(scope, &catch.body)
                         }
+                        ok => ok,
+                    };
+                }
+                if let Some(наконец) = наконец {
+                    // Ошибка или выход из блока в `наконец` заменяют то, чем закончилась попытка
+                    match self.execute_block(наконец)? {
+                        ControlFlow::Дальше => {}
+                        flow => return Ok(flow),
                     }
                 }
-                Ok(None)
+                result
             }
-            Stmt::Пока { условие, тело } => {
+            StmtKind::Бросить(expr) => {
+                let val = self.evaluate_expression(expr)?;
+                Err(self.thrown_error(val, expr.span, stmt.span))
+            }
+            StmtKind::Пока { условие, тело } => {
                 loop {
-                    let cond = self.evaluate_expression(условие)?;
-                    if let Value::Булево(false) = cond {
+                    if !self.evaluate_condition(условие, "пока")? {
                         break;
                     }
                     
-                    for s in тело {
-                        if let Some(ret) = self.execute_statement(s)? {
-                            return Ok(Some(ret));
-                        }
+                    match self.execute_block(тело)? {
+                        ControlFlow::Дальше | ControlFlow::Продолжить => {}
+                        ControlFlow::Прервать => break,
+                        flow @ ControlFlow::Вернуть(_) => return Ok(flow),
                     }
                 }
-                Ok(None)
+                Ok(ControlFlow::Дальше)
+            }
+            StmtKind::Для { переменная, начало, конец, шаг, тело } => {
+                let from = self.evaluate_bound(начало, "Начало диапазона")?;
+                let to = self.evaluate_bound(конец, "Конец диапазона")?;
+                let step = match шаг {
+                    Some(шаг) => {
+                        let step = self.evaluate_bound(шаг, "Шаг цикла")?;
+                        if step == 0.0 {
+                            return Err(error_at(шаг.span, codes::DOMAIN_ERROR, "Шаг цикла не может быть равен нулю"));
+                        }
+                        step
+                    }
+                    None => 1.0,
+                };
+                
+                // Число повторов считаем заранее: присваивание переменной
+                // цикла внутри тела не может сделать цикл бесконечным
+                let span = ((to - from) / step + 1e-9).floor();
+                let count = if span < 0.0 {
+                    0
+                } else {
+                    // Сравнение ложно и для бесконечности: `1e308 - (-1e308)` уже не число
+                    let count = if span < u64::MAX as f64 { (span as u64).checked_add(1) } else { None };
+                    match count {
+                        Some(count) => count,
+                        None => {
+                            let span = шаг.as_ref().map_or(конец.span, |шаг| шаг.span);
+                            return Err(error_at(span, codes::DOMAIN_ERROR, "Слишком много повторов цикла 'для'")
+                                .with_note(format!("от {} до {} с шагом {}", from, to, step)));
+                        }
+                    }
+                };
+                let values = (0..count).map(|i| Value::Число(from + i as f64 * step));
+                self.run_loop(переменная, values, тело)
             }
-            Stmt::Вернуть(expr) => {
+            StmtKind::ДляКаждого { переменная, коллекция, тело } => {
+                // Список перебирается в том виде, в каком был до начала цикла
+                let values: Vec<Value> = match self.evaluate_expression(коллекция)? {
+                    Value::Строка(s) => s.chars().map(|ch| Value::Строка(ch.to_string())).collect(),
+                    Value::Список(items) => items.borrow().clone(),
+                    Value::Словарь(entries) => entries.borrow().iter().map(|(key, _)| key.to_value()).collect(),
+                    other => {
+                        return Err(error_at(
+                            коллекция.span,
+                            codes::RUNTIME_TYPE_MISMATCH,
+                            format!("Перебирать в цикле можно только строку, список или словарь, получено значение типа {}", type_name(&other)),
+                        )
+                        .with_note(format!("значение: {}", repr_value(&other))));
+                    }
+                };
+                self.run_loop(переменная, values.into_iter(), тело)
+            }
+            StmtKind::Вернуть(expr) => {
                 let val = self.evaluate_expression(expr)?;
-                Ok(Some(val))
+                Ok(ControlFlow::Вернуть(val))
             }
-            Stmt::Выражение(expr) => {
+            StmtKind::Прервать => Ok(ControlFlow::Прервать),
+            StmtKind::Продолжить => Ok(ControlFlow::Продолжить),
+            StmtKind::Выражение(expr) => {
                 self.evaluate_expression(expr)?;
-                Ok(None)
+                Ok(ControlFlow::Дальше)
             }
-            Stmt::ОбъявлениеФункции { имя, параметры, тело } => {
-                self.functions.insert(имя.clone(), (параметры.clone(), тело.clone()));
-                Ok(None)
+            // Уже объявлена в начале своего блока
+            StmtKind::ОбъявлениеФункции { .. } => Ok(ControlFlow::Дальше),
+            StmtKind::ОбъявлениеСтруктуры { имя, поля } => {
+                let ty = StructType { name: имя.clone(), fields: поля.clone(), class: None };
+                self.structs.insert((stmt.span.file, имя.clone()), Rc::new(ty));
+                Ok(ControlFlow::Дальше)
+            }
+            StmtKind::ОбъявлениеКласса { имя, родитель, поля, методы } => {
+                let parent = match родитель {
+                    Some((name, span)) => Some(self.parent_class(name, *span)?),
+                    None => None,
+                };
+                let (mut fields, mut defaults) = match &parent {
+                    Some(parent) => {
+                        let class = parent.class.as_ref().expect("родитель — класс");
+                        (parent.fields.clone(), class.defaults.clone())
+                    }
+                    None => (Vec::new(), Vec::new()),
+                };
+                // Поле с именем родительского задаёт ему новое начальное значение
+                for field in поля {
+                    match fields.iter().position(|f| f.name == field.param.name) {
+                        Some(index) => {
+                            fields[index] = field.param.clone();
+                            defaults[index] = field.value.clone();
+                        }
+                        None => {
+                            fields.push(field.param.clone());
+                            defaults.push(field.value.clone());
+                        }
+                    }
+                }
+                let methods = методы
+                    .iter()
+                    .map(|method| {
+                        let function = UserFunction {
+                            name: Some(format!("{}.{}", имя, method.name)),
+                            params: method.function.params.clone(),
+                            return_type: method.function.result.clone(),
+                            body: method.function.body.clone(),
+                            closure: self.env.clone(),
+                        };
+                        (method.name.clone(), Rc::new(function))
+                    })
+                    .collect();
+                let class = Class { parent, defaults, methods, closure: self.env.clone() };
+                let ty = StructType { name: имя.clone(), fields, class: Some(class) };
+                self.structs.insert((stmt.span.file, имя.clone()), Rc::new(ty));
+                Ok(ControlFlow::Дальше)
             }
         }
     }
     
-    fn evaluate_expression(&mut self, expr: &Expr) -> Result<Value, String> {
-        match expr {
-            Expr::Число(n) => Ok(Value::Число(*n)),
-            Expr::Строка(s) => Ok(Value::Строка(s.clone())),
-            Expr::Булево(b) => Ok(Value::Булево(*b)),
-            Expr::Идентификатор(name) => {
-                self.variables
-                    .get(name)
-                    .cloned()
-                    .ok_or_else(|| format!("Переменная '{}' не найдена", name))
+    fn evaluate_expression(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        match &expr.kind {
+            ExprKind::Число(n) => Ok(Value::Число(*n)),
+            ExprKind::Строка(s) => Ok(Value::Строка(s.clone())),
+            ExprKind::Шаблон(parts) => {
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        TemplatePart::Текст(s) => text.push_str(s),
+                        TemplatePart::Выражение(expr) => {
+                            text.push_str(&format_value(&self.evaluate_expression(expr)?));
+                        }
+                    }
+                }
+                Ok(Value::Строка(text))
+            }
+            ExprKind::Булево(b) => Ok(Value::Булево(*b)),
+            ExprKind::Идентификатор(name) => match self.env.borrow().get(name) {
+                Some(val) => Ok(val),
+                None => Err(self.undefined_variable(name, expr.span)),
+            },
+            ExprKind::Этот => match self.env.borrow().get("этот") {
+                Some(val) => Ok(val),
+                None => Err(error_at(expr.span, codes::UNDEFINED_VARIABLE, "'этот' можно использовать только внутри метода класса")),
+            },
+            ExprKind::Лямбда(lambda) => {
+                let function = UserFunction {
+                    name: None,
+                    params: lambda.params.clone(),
+                    return_type: lambda.result.clone(),
+                    body: lambda.body.clone(),
+                    closure: self.env.clone(),
+                };
+                Ok(Value::Функция(Rc::new(function)))
+            }
+            ExprKind::БинарнаяОперация { левый, оператор: оператор @ (BinOp::И | BinOp::Или), правый } => {
+                // Правый операнд вычисляется, только если от него зависит результат
+                let left = self.evaluate_operand(левый, оператор.symbol())?;
+                if left == matches!(оператор, BinOp::Или) {
+                    return Ok(Value::Булево(left));
+                }
+                Ok(Value::Булево(self.evaluate_operand(правый, оператор.symbol())?))
+            }
+            ExprKind::Отрицание(операнд) => {
+                Ok(Value::Булево(!self.evaluate_operand(операнд, "не")?))
             }
-            Expr::БинарнаяОперация { левый, оператор, правый } => {
+            ExprKind::БинарнаяОперация { левый, оператор, правый } => {
                 let left = self.evaluate_expression(левый)?;
                 let right = self.evaluate_expression(правый)?;
                 
@@ -134,11 +646,36 @@ impl Interpreter {
                     (Value::Число(l), BinOp::Умножить, Value::Число(r)) => Ok(Value::Число(l * r)),
                     (Value::Число(l), BinOp::Разделить, Value::Число(r)) => {
                         if r == 0.0 {
-                            Err("Деление на ноль".to_string())
+                            Err(error_at(expr.span, codes::DIVISION_BY_ZERO, "Деление на ноль"))
                         } else {
                             Ok(Value::Число(l / r))
                         }
                     }
+                    (Value::Число(l), BinOp::Остаток, Value::Число(r)) => {
+                        if r == 0.0 {
+                            Err(error_at(expr.span, codes::DIVISION_BY_ZERO, "Остаток от деления на ноль"))
+                        } else {
+                            Ok(Value::Число(l % r))
+                        }
+                    }
+                    (Value::Число(l), BinOp::ЦелоеДеление, Value::Число(r)) => {
+                        if r == 0.0 {
+                            Err(error_at(expr.span, codes::DIVISION_BY_ZERO, "Целочисленное деление на ноль"))
+                        } else {
+                            // Как div в Паскале: дробная часть отбрасывается, `a = b * (a div b) + a % b`
+                            Ok(Value::Число((l / r).trunc()))
+                        }
+                    }
+                    (Value::Число(l), BinOp::Степень, Value::Число(r)) => {
+                        let result = l.powf(r);
+                        if result.is_nan() {
+                            Err(error_at(expr.span, codes::DOMAIN_ERROR, format!(
+                                "Нельзя возвести отрицательное число {} в дробную степень {}", l, r
+                            )))
+                        } else {
+                            Ok(Value::Число(result))
+                        }
+                    }
                     (Value::Число(l), BinOp::Равно, Value::Число(r)) => Ok(Value::Булево(l == r)),
                     (Value::Число(l), BinOp::НеРавно, Value::Число(r)) => Ok(Value::Булево(l != r)),
                     (Value::Число(l), BinOp::Больше, Value::Число(r)) => Ok(Value::Булево(l > r)),
@@ -149,11 +686,31 @@ impl Interpreter {
                     (Value::Строка(l), BinOp::Плюс, Value::Строка(r)) => {
                         Ok(Value::Строка(format!("{}{}", l, r)))
                     }
+                    (Value::Строка(l), BinOp::Равно, Value::Строка(r)) => Ok(Value::Булево(l == r)),
+                    (Value::Строка(l), BinOp::НеРавно, Value::Строка(r)) => Ok(Value::Булево(l != r)),
+                    (Value::Список(l), BinOp::Плюс, Value::Список(r)) => {
+                        let items = l.borrow().iter().chain(r.borrow().iter()).cloned().collect();
+                        Ok(Value::Список(Rc::new(RefCell::new(items))))
+                    }
+                    (Value::Булево(l), BinOp::Равно, Value::Булево(r)) => Ok(Value::Булево(l == r)),
+                    (Value::Булево(l), BinOp::НеРавно, Value::Булево(r)) => Ok(Value::Булево(l != r)),
                     
-                    _ => Err("Неподдерживаемая операция".to_string()),
+                    _ => Err(error_at(expr.span, codes::UNSUPPORTED_OPERATION, "Неподдерживаемая операция")),
                 }
             }
-            Expr::ВызовФункции { имя, аргументы } => {
+            ExprKind::ВызовФункции { имя, аргументы } => {
+                // Имя из области видимости — функция, параметр или переменная — заслоняет
+                // одноимённую встроенную функцию, как и в `Идентификатор`
+                let local = self.env.borrow().get(имя);
+                if let Some(val) = local {
+                    let function = as_function(val, expr.span)?;
+                    let mut arg_values = Vec::with_capacity(аргументы.len());
+                    for arg in аргументы {
+                        arg_values.push(self.evaluate_expression(arg)?);
+                    }
+                    return self.call_function(&function, arg_values, expr.span, None);
+                }
+                
                 // --- 1. Встроенная функция ПЕЧАТЬ ---
                 if имя == "печать" {
                     let mut output_parts = Vec::new();
@@ -168,37 +725,35 @@ impl Interpreter {
                 // --- 2. Встроенная функция ГРАФИК ---
                 if имя == "график" {
                     if аргументы.len() < 3 {
-                        return Err("график требует: функция, от, до".to_string());
+                        return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "график требует: функция, от, до"));
                     }
                     
-                    let func_name = match &аргументы[0] {
-                        Expr::Идентификатор(name) => name.clone(),
-                        _ => return Err("Первый аргумент должен быть именем функции".to_string()),
+                    let function = match self.evaluate_expression(&аргументы[0])? {
+                        Value::Функция(function) => function,
+                        other => return Err(error_at(аргументы[0].span, codes::ARGUMENT_TYPE, format!(
+                            "Первый аргумент должен быть функцией, получено значение типа {}", type_name(&other)
+                        ))),
+                    };
+                    // Подпись графика: имя, под которым функция передана
+                    let label = match &аргументы[0].kind {
+                        ExprKind::Идентификатор(name) => name.clone(),
+                        _ => function.name().to_string(),
                     };
                     
                     let from = match self.evaluate_expression(&аргументы[1])? {
                         Value::Число(n) => n,
-                        _ => return Err("Второй аргумент должен быть числом".to_string()),
+                        _ => return Err(error_at(аргументы[1].span, codes::ARGUMENT_TYPE, "Второй аргумент должен быть числом")),
                     };
                     
                     let to = match self.evaluate_expression(&аргументы[2])? {
                         Value::Число(n) => n,
-                        _ => return Err("Третий аргумент должен быть числом".to_string()),
+                        _ => return Err(error_at(аргументы[2].span, codes::ARGUMENT_TYPE, "Третий аргумент должен быть числом")),
                     };
                     
-                    // Получить функцию
-                    let (params, body) = self.functions.get(&func_name).cloned()
-                        .ok_or_else(|| format!("Функция '{}' не найдена", func_name))?;
-                    
-                    if params.len() != 1 {
-                        return Err(format!("Функция для графика должна иметь 1 параметр, получено {}", params.len()));
+                    if function.params.len() != 1 {
+                        return Err(error_at(аргументы[0].span, codes::ARGUMENT_COUNT, format!("Функция для графика должна иметь 1 параметр, получено {}", function.params.len())));
                     }
                     
-                    let param_name = params[0].clone();
-                    
-                    // Сохранить глобальные переменные
-                    let saved_globals = self.variables.clone();
-                    
                     // Вычислить точки
                     let steps = 200;
                     let step = (to - from) / steps as f64;
@@ -207,18 +762,12 @@ impl Interpreter {
                     for i in 0..=steps {
                         let x = from + i as f64 * step;
                         
-                        // Создать чистую область видимости для вычисления точки
-                        self.variables.clear();
-                        self.variables.insert(param_name.clone(), Value::Число(x));
-                        
-                        // Выполнить функцию
-                        let mut result = Value::Пусто;
-                        for stmt in &body {
-                            if let Some(ret) = self.execute_statement(stmt)? {
-                                result = ret;
-                                break;
-                            }
-                        }
+                        let result = self.call_function(
+                            &function,
+                            vec![Value::Число(x)],
+                            expr.span,
+                            None,
+                        )?;
                         
                         if let Value::Число(y) = result {
                             if y.is_finite() {
@@ -227,9 +776,6 @@ impl Interpreter {
                         }
                     }
                     
-                    // Восстановить глобальные переменные
-                    self.variables = saved_globals;
-                    
                     // Отправить график
                     if let Some(ref mut handler) = self.plot_handler {
                         let timestamp = SystemTime::now()
@@ -240,7 +786,7 @@ impl Interpreter {
                         handler(PlotData {
                             points,
                             color: "#0066cc".to_string(),
-                            label: func_name,
+                            label,
                             timestamp,
                         });
                     }
@@ -258,125 +804,1180 @@ impl Interpreter {
                 match имя.as_str() {
                     "син" => {
                         if arg_values.len() != 1 {
-                            return Err("син ожидает 1 аргумент".to_string());
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "син ожидает 1 аргумент"));
                         }
                         if let Value::Число(x) = arg_values[0] {
                             Ok(Value::Число(x.sin()))
                         } else {
-                            Err("Аргумент син должен быть числом".to_string())
+                            Err(error_at(expr.span, codes::ARGUMENT_TYPE, "Аргумент син должен быть числом"))
                         }
                     }
                     "кос" => {
                         if arg_values.len() != 1 {
-                            return Err("кос ожидает 1 аргумент".to_string());
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "кос ожидает 1 аргумент"));
                         }
                         if let Value::Число(x) = arg_values[0] {
                             Ok(Value::Число(x.cos()))
                         } else {
-                            Err("Аргумент кос должен быть числом".to_string())
+                            Err(error_at(expr.span, codes::ARGUMENT_TYPE, "Аргумент кос должен быть числом"))
                         }
                     }
                     "эксп" => {
                         if arg_values.len() != 1 {
-                            return Err("эксп ожидает 1 аргумент".to_string());
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "эксп ожидает 1 аргумент"));
                         }
                         if let Value::Число(x) = arg_values[0] {
                             Ok(Value::Число(x.exp()))
                         } else {
-                            Err("Аргумент эксп должен быть числом".to_string())
+                            Err(error_at(expr.span, codes::ARGUMENT_TYPE, "Аргумент эксп должен быть числом"))
                         }
                     }
                     "лог" => {
                         if arg_values.len() != 1 {
-                            return Err("лог ожидает 1 аргумент".to_string());
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "лог ожидает 1 аргумент"));
                         }
                         if let Value::Число(x) = arg_values[0] {
                             if x <= 0.0 {
-                                Err("Аргумент лог должен быть >0".to_string())
+                                Err(error_at(expr.span, codes::DOMAIN_ERROR, "Аргумент лог должен быть >0"))
                             } else {
                                 Ok(Value::Число(x.ln()))
                             }
                         } else {
-                            Err("Аргумент лог должен быть числом".to_string())
+                            Err(error_at(expr.span, codes::ARGUMENT_TYPE, "Аргумент лог должен быть числом"))
                         }
                     }
                     "корень" => {
                         if arg_values.len() != 1 {
-                            return Err("корень ожидает 1 аргумент".to_string());
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "корень ожидает 1 аргумент"));
                         }
                         if let Value::Число(x) = arg_values[0] {
                             if x < 0.0 {
-                                Err("Аргумент корень должен быть >=0".to_string())
+                                Err(error_at(expr.span, codes::DOMAIN_ERROR, "Аргумент корень должен быть >=0"))
                             } else {
                                 Ok(Value::Число(x.sqrt()))
                             }
                         } else {
-                            Err("Аргумент корень должен быть числом".to_string())
+                            Err(error_at(expr.span, codes::ARGUMENT_TYPE, "Аргумент корень должен быть числом"))
                         }
                     }
-                    // Пользовательские функции
+                    "длина" => {
+                        if arg_values.len() != 1 {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "длина ожидает 1 аргумент"));
+                        }
+                        match &arg_values[0] {
+                            Value::Строка(s) => Ok(Value::Число(s.chars().count() as f64)),
+                            Value::Список(items) => Ok(Value::Число(items.borrow().len() as f64)),
+                            Value::Словарь(entries) => Ok(Value::Число(entries.borrow().len() as f64)),
+                            _ => Err(error_at(аргументы[0].span, codes::ARGUMENT_TYPE, "Аргумент длина должен быть строкой, списком или словарём")),
+                        }
+                    }
+                    "добавить" => {
+                        if arg_values.len() != 2 {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "добавить ожидает 2 аргумента: список и значение"));
+                        }
+                        let items = as_list(&arg_values[0], аргументы[0].span, "добавить")?;
+                        items.borrow_mut().push(arg_values[1].clone());
+                        Ok(Value::Пусто)
+                    }
+                    "удалить" => {
+                        if arg_values.len() != 2 {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "удалить ожидает 2 аргумента: список и индекс или словарь и ключ"));
+                        }
+                        if let Value::Словарь(entries) = &arg_values[0] {
+                            let key = dict_key(&arg_values[1], аргументы[1].span)?;
+                            let mut entries = entries.borrow_mut();
+                            return match entries.iter().position(|(k, _)| *k == key) {
+                                Some(position) => Ok(entries.remove(position).1),
+                                None => Err(missing_key(&entries, &key, аргументы[1].span)),
+                            };
+                        }
+                        let items = as_list(&arg_values[0], аргументы[0].span, "удалить")?;
+                        let len = items.borrow().len();
+                        let index = list_index(&arg_values[1], len, аргументы[1].span, false)?;
+                        let removed = items.borrow_mut().remove(index);
+                        Ok(removed)
+                    }
+                    "вставить" => {
+                        if arg_values.len() != 3 {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "вставить ожидает 3 аргумента: список, индекс и значение"));
+                        }
+                        let items = as_list(&arg_values[0], аргументы[0].span, "вставить")?;
+                        let len = items.borrow().len();
+                        // Вставить можно и в самый конец списка
+                        let index = list_index(&arg_values[1], len, аргументы[1].span, true)?;
+                        items.borrow_mut().insert(index, arg_values[2].clone());
+                        Ok(Value::Пусто)
+                    }
+                    "ключи" | "значения" => {
+                        if arg_values.len() != 1 {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, format!("{} ожидает 1 аргумент", имя)));
+                        }
+                        let entries = as_dict(&arg_values[0], аргументы[0].span, имя)?;
+                        let items = entries
+                            .borrow()
+                            .iter()
+                            .map(|(key, val)| if имя == "ключи" { key.to_value() } else { val.clone() })
+                            .collect();
+                        Ok(Value::Список(Rc::new(RefCell::new(items))))
+                    }
+                    "содержит" => {
+                        if arg_values.len() != 2 {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, "содержит ожидает 2 аргумента: словарь и ключ"));
+                        }
+                        let entries = as_dict(&arg_values[0], аргументы[0].span, имя)?;
+                        let key = dict_key(&arg_values[1], аргументы[1].span)?;
+                        let found = entries.borrow().iter().any(|(k, _)| *k == key);
+                        Ok(Value::Булево(found))
+                    }
+                    // Структуры и классы: вызов по имени создаёт значение
                     _ => {
-                        if let Some((params, body)) = self.functions.get(имя).cloned() {
-                            if params.len() != arg_values.len() {
-                                return Err(format!(
-                                    "Функция '{}' ожидает {} аргументов, получено {}",
-                                    имя, params.len(), arg_values.len()
-                                ));
-                            }
-                            
-                            // Сохранить текущие переменные (изоляция scope, как в оригинале)
-                            let old_vars = self.variables.clone();
-                            
-                            // Установить параметры
-                            // Очищаем переменные, чтобы эмулировать локальную область видимости (если такова задумка)
-                            // Если вы хотите доступ к глобальным переменным внутри функции, 
-                            // уберите self.variables.clear() и просто вставьте параметры.
-                            // Но судя по коду графика, вы используете изоляцию.
-                            
-                            // Вариант 1: Полная изоляция (только параметры доступны)
-                             self.variables.clear(); 
-                            
-                            // Вариант 2 (обычный): Новая область видимости + доступ к глобальным (раскомментировать при желании)
-                            // self.variables = old_vars.clone(); 
-                            
-                            for (param, val) in params.iter().zip(arg_values.iter()) {
-                                self.variables.insert(param.clone(), val.clone());
-                            }
-                            
-                            // Выполнить функцию
-                            let mut result = Value::Пусто;
-                            for stmt in &body {
-                                if let Some(ret) = self.execute_statement(stmt)? {
-                                    result = ret;
-                                    break;
-                                }
-                            }
-                            
-                            // Восстановить переменные
-                            self.variables = old_vars;
-                            
-                            Ok(result)
-                        } else {
-                            Err(format!("Функция '{}' не найдена", имя))
+                        let Some(ty) = self.struct_type(имя, expr.span) else {
+                            return Err(self.undefined_function(имя, expr.span));
+                        };
+                        if ty.class.is_some() {
+                            return self.new_object(ty, arg_values, expr.span);
+                        }
+                        if arg_values.len() != ty.fields.len() {
+                            return Err(error_at(expr.span, codes::ARGUMENT_COUNT, format!(
+                                "У структуры '{}' полей: {}, а передано значений: {}",
+                                имя, ty.fields.len(), arg_values.len()
+                            )));
+                        }
+                        let spans: Vec<Span> = аргументы.iter().map(|arg| arg.span).collect();
+                        new_structure(ty, arg_values, &spans)
+                    }
+                }
+            }
+            ExprKind::Вызов { функция, аргументы } => {
+                let function = as_function(self.evaluate_expression(функция)?, функция.span)?;
+                let mut arg_values = Vec::with_capacity(аргументы.len());
+                for arg in аргументы {
+                    arg_values.push(self.evaluate_expression(arg)?);
+                }
+                self.call_function(&function, arg_values, expr.span, None)
+            }
+            // Метод класса, а если такого нет — функция, лежащая в поле
+            ExprKind::ВызовМетода { объект, метод, аргументы } => {
+                let target = self.evaluate_expression(объект)?;
+                let structure = as_structure(target.clone(), метод, объект.span)?;
+                let method = structure.borrow().ty.method(метод);
+                let (function, receiver) = match method {
+                    Some(method) => (method, Some(target)),
+                    None => {
+                        let structure = structure.borrow();
+                        if structure.ty.class.is_some() && structure.ty.field_index(метод).is_none() {
+                            return Err(unknown_method(&structure.ty, метод, expr.span));
+                        }
+                        let index = field_index(&structure.ty, метод, expr.span)?;
+                        (as_function(structure.fields[index].clone(), expr.span)?, None)
+                    }
+                };
+                let mut arg_values = Vec::with_capacity(аргументы.len());
+                for arg in аргументы {
+                    arg_values.push(self.evaluate_expression(arg)?);
+                }
+                self.call_function(&function, arg_values, expr.span, receiver)
+            }
+            ExprKind::СозданиеСтруктуры { имя, поля } => {
+                let Some(ty) = self.struct_type(имя, expr.span) else {
+                    return Err(error_at(expr.span, codes::UNDEFINED_FUNCTION, format!("Структура '{}' не найдена", имя)));
+                };
+                if ty.class.is_some() {
+                    return Err(error_at(expr.span, codes::UNSUPPORTED_OPERATION, format!(
+                        "Объект класса '{}' создаётся вызовом конструктора: {}(...)", имя, имя
+                    )));
+                }
+                let mut values: Vec<Option<Value>> = vec![None; ty.fields.len()];
+                let mut spans = vec![expr.span; ty.fields.len()];
+                for field in поля {
+                    let index = field_index(&ty, &field.name, field.span)?;
+                    if values[index].is_some() {
+                        return Err(error_at(field.span, codes::UNKNOWN_FIELD, format!(
+                            "Поле '{}' задано дважды", field.name
+                        )));
+                    }
+                    values[index] = Some(self.evaluate_expression(&field.value)?);
+                    spans[index] = field.value.span;
+                }
+                let missing: Vec<&str> = ty
+                    .field_names()
+                    .zip(&values)
+                    .filter(|(_, val)| val.is_none())
+                    .map(|(name, _)| name)
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(error_at(expr.span, codes::UNKNOWN_FIELD, format!(
+                        "Не заданы поля структуры '{}': {}", имя, missing.join(", ")
+                    )));
+                }
+                new_structure(ty, values.into_iter().flatten().collect(), &spans)
+            }
+            ExprKind::Поле { объект, поле } => {
+                let structure = as_structure(self.evaluate_expression(объект)?, поле, объект.span)?;
+                let structure = structure.borrow();
+                let index = field_index(&structure.ty, поле, expr.span)?;
+                Ok(structure.fields[index].clone())
+            }
+            ExprKind::ПрисваиваниеПолю { объект, поле, значение } => {
+                let structure = as_structure(self.evaluate_expression(объект)?, поле, объект.span)?;
+                let val = self.evaluate_expression(значение)?;
+                let mut structure = structure.borrow_mut();
+                let ty = structure.ty.clone();
+                let index = field_index(&ty, поле, expr.span)?;
+                if let Some(annotation) = &ty.fields[index].ty {
+                    check_type(&val, annotation, значение.span, || {
+                        format!("Поле '{}' {} '{}'", поле, ty.kind(), ty.name)
+                    })?;
+                }
+                structure.fields[index] = val.clone();
+                Ok(val)
+            }
+            ExprKind::Присваивание { имя, значение } => {
+                let val = self.evaluate_expression(значение)?;
+                let assigned = self.env.borrow_mut().assign(имя, val.clone());
+                if !assigned {
+                    return Err(error_at(
+                        expr.span,
+                        codes::UNDEFINED_VARIABLE,
+                        format!("Переменная '{}' не объявлена", имя),
+                    )
+                    .with_note(format!("новую переменную объявляют так: пусть {} = ...;", имя)));
+                }
+                Ok(val)
+            }
+            ExprKind::Список(элементы) => {
+                let mut items = Vec::with_capacity(элементы.len());
+                for item in элементы {
+                    items.push(self.evaluate_expression(item)?);
+                }
+                Ok(Value::Список(Rc::new(RefCell::new(items))))
+            }
+            ExprKind::Словарь(пары) => {
+                let mut entries: Vec<(Key, Value)> = Vec::with_capacity(пары.len());
+                for (ключ, значение) in пары {
+                    let key = dict_key(&self.evaluate_expression(ключ)?, ключ.span)?;
+                    let val = self.evaluate_expression(значение)?;
+                    // Повторный ключ в литерале заменяет прежнее значение
+                    match entries.iter_mut().find(|(k, _)| *k == key) {
+                        Some(entry) => entry.1 = val,
+                        None => entries.push((key, val)),
+                    }
+                }
+                Ok(Value::Словарь(Rc::new(RefCell::new(entries))))
+            }
+            ExprKind::Индекс { объект, индекс } => {
+                let container = self.evaluate_expression(объект)?;
+                let index = self.evaluate_expression(индекс)?;
+                match container {
+                    Value::Список(items) => {
+                        let items = items.borrow();
+                        let index = list_index(&index, items.len(), индекс.span, false)?;
+                        Ok(items[index].clone())
+                    }
+                    Value::Словарь(entries) => {
+                        let key = dict_key(&index, индекс.span)?;
+                        let entries = entries.borrow();
+                        match entries.iter().find(|(k, _)| *k == key) {
+                            Some((_, val)) => Ok(val.clone()),
+                            None => Err(missing_key(&entries, &key, индекс.span)),
                         }
                     }
+                    other => Err(not_indexable(&other, объект.span)),
                 }
             }
-            Expr::Присваивание { имя, значение } => {
+            ExprKind::ПрисваиваниеПоИндексу { объект, индекс, значение } => {
+                let container = self.evaluate_expression(объект)?;
+                let index = self.evaluate_expression(индекс)?;
                 let val = self.evaluate_expression(значение)?;
-                self.variables.insert(имя.clone(), val.clone());
+                match container {
+                    Value::Список(items) => {
+                        let len = items.borrow().len();
+                        let index = list_index(&index, len, индекс.span, false)?;
+                        items.borrow_mut()[index] = val.clone();
+                    }
+                    // Присваивание по новому ключу добавляет его в словарь
+                    Value::Словарь(entries) => {
+                        let key = dict_key(&index, индекс.span)?;
+                        let mut entries = entries.borrow_mut();
+                        match entries.iter_mut().find(|(k, _)| *k == key) {
+                            Some(entry) => entry.1 = val.clone(),
+                            None => entries.push((key, val.clone())),
+                        }
+                    }
+                    other => return Err(not_indexable(&other, объект.span)),
+                }
                 Ok(val)
             }
+            ExprKind::Ошибка => Err(error_at(expr.span, codes::UNSUPPORTED_OPERATION, "Выражение содержит ошибку")),
+        }
+    }
+    
+    // Тело цикла `для` по очереди для каждого значения переменной. У каждого
+    // повтора своя область, в ней видна переменная цикла.
+    fn run_loop(
+        &mut self,
+        variable: &str,
+        values: impl Iterator<Item = Value>,
+        body: &[Stmt],
+    ) -> Result<ControlFlow, RuntimeError> {
+        for value in values {
+            let scope = Environment::child(&self.env);
+            scope.borrow_mut().define(variable, value);
+            match self.execute_in(scope, body)? {
+                ControlFlow::Дальше | ControlFlow::Продолжить => {}
+                ControlFlow::Прервать => break,
+                flow @ ControlFlow::Вернуть(_) => return Ok(flow),
+            }
+        }
+        Ok(ControlFlow::Дальше)
+    }
+    
+    fn evaluate_number(&mut self, expr: &Expr, what: &str) -> Result<f64, RuntimeError> {
+        match self.evaluate_expression(expr)? {
+            Value::Число(n) => Ok(n),
+            other => Err(error_at(
+                expr.span,
+                codes::RUNTIME_TYPE_MISMATCH,
+                format!("{}: ожидается тип число, получено значение типа {}", what, type_name(&other)),
+            )
+            .with_note(format!("значение: {}", repr_value(&other)))),
+        }
+    }
+    
+    // Граница или шаг цикла `для`: бесконечность и не-число не дают узнать
+    // число повторов
+    fn evaluate_bound(&mut self, expr: &Expr, what: &str) -> Result<f64, RuntimeError> {
+        let n = self.evaluate_number(expr, what)?;
+        if !n.is_finite() {
+            return Err(error_at(
+                expr.span,
+                codes::DOMAIN_ERROR,
+                format!(
+                    "{}: ожидается конечное число, получено {}",
+                    what,
+                    if n.is_nan() { "не-число" } else { "бесконечность" }
+                ),
+            ));
+        }
+        Ok(n)
+    }
+    
+    // Операнд логической операции: только истина или ложь
+    fn evaluate_operand(&mut self, expr: &Expr, op: &str) -> Result<bool, RuntimeError> {
+        let value = self.evaluate_expression(expr)?;
+        expect_boolean(value, expr.span, || format!("Операнд '{}' должен иметь тип булево", op))
+    }
+    
+    // Условие `если` и `пока`: как и при проверке типов, только истина или ложь
+    fn evaluate_condition(&mut self, expr: &Expr, statement: &str) -> Result<bool, RuntimeError> {
+        let value = self.evaluate_expression(expr)?;
+        expect_boolean(value, expr.span, || format!("Условие '{}' должно иметь тип булево", statement))
+    }
+    
+    fn undefined_variable(&self, name: &str, span: Span) -> RuntimeError {
+        let err = error_at(span, codes::UNDEFINED_VARIABLE, format!("Переменная '{}' не найдена", name));
+        let names = self.env.borrow().names(false);
+        let known = names.iter().map(String::as_str);
+        let functions = self.function_names();
+        match suggest::variable_hint(name, known, functions.iter().map(String::as_str)) {
+            Some(hint) => err.with_note(hint),
+            None => err,
+        }
+    }
+    
+    fn undefined_function(&self, name: &str, span: Span) -> RuntimeError {
+        let err = error_at(span, codes::UNDEFINED_FUNCTION, format!("Функция '{}' не найдена", name));
+        let functions = self.function_names();
+        match suggest::function_hint(name, functions.iter().map(String::as_str)) {
+            Some(hint) => err.with_note(hint),
+            None => err,
+        }
+    }
+    
+    // Пойманная ошибка как значение структуры `Ошибка`; трассировка теряется
+    fn error_value(&self, err: RuntimeError) -> Value {
+        let fields = vec![
+            Value::Строка(err.message),
+            Value::Строка(err.code.into()),
+            Value::Число(err.span.line as f64),
+            Value::Число(err.span.column as f64),
+        ];
+        Value::Структура(Rc::new(RefCell::new(Structure { ty: self.error_type.clone(), fields })))
+    }
+    
+    // Ошибка из `бросить`: строка — сообщение новой ошибки, структура
+    // `Ошибка` — ошибка, пойманная раньше или созданная программой
+    fn thrown_error(&self, val: Value, value_span: Span, span: Span) -> RuntimeError {
+        let error = match &val {
+            Value::Строка(message) => return error_at(span, codes::USER_ERROR, message.clone()),
+            Value::Структура(s) if Rc::ptr_eq(&s.borrow().ty, &self.error_type) => s.borrow(),
+            other => {
+                return error_at(
+                    value_span,
+                    codes::RUNTIME_TYPE_MISMATCH,
+                    format!("Бросить можно строку или структуру Ошибка, получено значение типа {}", type_name(other)),
+                )
+                .with_note(format!("значение: {}", repr_value(other)));
+            }
+        };
+        let (Value::Строка(message), Value::Строка(code)) = (&error.fields[0], &error.fields[1]) else {
+            return error_at(
+                value_span,
+                codes::RUNTIME_TYPE_MISMATCH,
+                "Поля 'сообщение' и 'код' ошибки должны быть строками",
+            )
+            .with_note(format!("значение: {}", repr_value(&val)));
+        };
+        let mut err = error_at(span, codes::USER_ERROR, message.clone());
+        err.code = code.as_str().into();
+        // Место, где ошибка случилась впервые, если её бросают повторно
+        if let (Value::Число(line), Value::Число(column)) = (&error.fields[2], &error.fields[3]) {
+            if *line >= 1.0 && (*line as u32, *column as u32) != (span.line, span.column) {
+                err = err.with_note(format!("ошибка возникла в строке {}, столбце {}", line, column));
+            }
+        }
+        err
+    }
+    
+    // Встроенные и объявленные пользователем функции
+    fn function_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTINS.iter().map(|name| name.to_string()).collect();
+        names.extend(self.env.borrow().names(true));
+        names
+    }
+    
+    /// Вызов пользовательской функции; у метода `receiver` — объект,
+    /// который внутри виден как `этот`. Если внутри случилась ошибка,
+    /// к её трассировке добавляется кадр этого вызова.
+    fn call_function(
+        &mut self,
+        function: &UserFunction,
+        args: Vec<Value>,
+        call_site: Span,
+        receiver: Option<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let name = function.name();
+        if function.params.len() != args.len() {
+            return Err(error_at(call_site, codes::ARGUMENT_COUNT, format!(
+                "Функция '{}' ожидает {} аргументов, получено {}",
+                name, function.params.len(), args.len()
+            )));
+        }
+        
+        let frame = StackFrame {
+            function: name.to_string(),
+            span: call_site,
+            arguments: args.iter().map(repr_value).collect(),
+        };
+        
+        if self.call_depth >= MAX_CALL_DEPTH {
+            let mut err = error_at(
+                call_site,
+                codes::RECURSION_LIMIT,
+                format!("Слишком глубокая рекурсия: более {} вложенных вызовов", MAX_CALL_DEPTH),
+            );
+            err.trace.push(frame);
+            return Err(err);
+        }
+        
+        // Аргументы неаннотированного кода проверяются только здесь, на входе в функцию
+        for (param, val) in function.params.iter().zip(&args) {
+            if let Some(ty) = &param.ty {
+                check_type(val, ty, call_site, || format!("Параметр '{}' функции '{}'", param.name, name))
+                    .map_err(|mut err| {
+                        err.trace.push(frame.clone());
+                        err
+                    })?;
+            }
+        }
+        
+        // Параметры — переменные новой области внутри той, где функция создана
+        let frame_scope = Environment::child(&function.closure);
+        for (param, val) in function.params.iter().zip(args) {
+            frame_scope.borrow_mut().define(&param.name, val);
+        }
+        // `этот` — ключевое слово, поэтому переменная с таким именем ни с чем не совпадёт
+        if let Some(receiver) = receiver {
+            frame_scope.borrow_mut().define("этот", receiver);
+        }
+        
+        self.call_depth += 1;
+        // `прервать` и `продолжить` вне цикла отсекает статическая проверка
+        let mut result = self.execute_in(frame_scope, &function.body).map(|flow| match flow {
+            ControlFlow::Вернуть(val) => val,
+            _ => Value::Пусто,
+        });
+        self.call_depth -= 1;
+        
+        if let (Ok(val), Some(ty)) = (&result, &function.return_type) {
+            if let Err(err) = check_type(val, ty, call_site, || format!("Результат функции '{}'", name)) {
+                result = Err(err);
+            }
+        }
+        
+        result.map_err(|mut err| {
+            err.trace.push(frame);
+            err
+        })
+    }
+    
+    // Структура или класс `name`, видимые в файле, где записано `span`
+    fn struct_type(&self, name: &str, span: Span) -> Option<Rc<StructType>> {
+        match self.structs.get(&(span.file, name.to_string())) {
+            Some(ty) => Some(ty.clone()),
+            None => (name == ERROR_STRUCT).then(|| self.error_type.clone()),
+        }
+    }
+    
+    // Класс, от которого наследуют; он должен быть объявлен раньше наследника
+    fn parent_class(&self, name: &str, span: Span) -> Result<Rc<StructType>, RuntimeError> {
+        match self.struct_type(name, span) {
+            Some(ty) if ty.class.is_some() => Ok(ty),
+            Some(_) => Err(error_at(span, codes::UNSUPPORTED_OPERATION, format!(
+                "Наследовать можно только от класса, а '{}' — структура", name
+            ))),
+            None => Err(error_at(span, codes::UNDEFINED_FUNCTION, format!("Класс '{}' не найден", name))
+                .with_note("класс-родитель объявляют раньше наследника")),
+        }
+    }
+    
+    /// Новый объект класса: поля получают начальные значения, потом
+    /// вызывается конструктор, свой или унаследованный.
+    fn new_object(&mut self, ty: Rc<StructType>, args: Vec<Value>, call_site: Span) -> Result<Value, RuntimeError> {
+        let class = ty.class.as_ref().expect("объект создаётся только у класса");
+        let mut fields = Vec::with_capacity(ty.fields.len());
+        for (field, default) in ty.fields.iter().zip(&class.defaults) {
+            let val = match default {
+                Some(expr) => {
+                    let outer = std::mem::replace(&mut self.env, class.closure.clone());
+                    let val = self.evaluate_expression(expr);
+                    self.env = outer;
+                    let val = val?;
+                    if let Some(annotation) = &field.ty {
+                        check_type(&val, annotation, expr.span, || {
+                            format!("Поле '{}' класса '{}'", field.name, ty.name)
+                        })?;
+                    }
+                    val
+                }
+                None => Value::Пусто,
+            };
+            fields.push(val);
+        }
+        
+        let object = Value::Структура(Rc::new(RefCell::new(Structure { ty: ty.clone(), fields })));
+        match ty.method(CONSTRUCTOR) {
+            Some(constructor) => {
+                self.call_function(&constructor, args, call_site, Some(object.clone()))?;
+            }
+            None if !args.is_empty() => {
+                return Err(error_at(call_site, codes::ARGUMENT_COUNT, format!(
+                    "У класса '{}' нет конструктора, объект создаётся без аргументов: {}()",
+                    ty.name, ty.name
+                )));
+            }
+            None => {}
+        }
+        Ok(object)
+    }
+}
+
+fn as_list(val: &Value, span: Span, function: &str) -> Result<Rc<RefCell<Vec<Value>>>, RuntimeError> {
+    match val {
+        Value::Список(items) => Ok(items.clone()),
+        other => Err(error_at(
+            span,
+            codes::ARGUMENT_TYPE,
+            format!("Первый аргумент {} должен быть списком, получено значение типа {}", function, type_name(other)),
+        )),
+    }
+}
+
+// Значение, которое вызывают как функцию
+fn as_function(val: Value, span: Span) -> Result<Rc<UserFunction>, RuntimeError> {
+    match val {
+        Value::Функция(function) => Ok(function),
+        other => Err(error_at(
+            span,
+            codes::RUNTIME_TYPE_MISMATCH,
+            format!("Вызвать можно только функцию, получено значение типа {}", type_name(&other)),
+        )
+        .with_note(format!("значение: {}", repr_value(&other)))),
+    }
+}
+
+fn as_structure(val: Value, field: &str, span: Span) -> Result<Rc<RefCell<Structure>>, RuntimeError> {
+    match val {
+        Value::Структура(structure) => Ok(structure),
+        other => Err(error_at(
+            span,
+            codes::RUNTIME_TYPE_MISMATCH,
+            format!("Поле '.{}' есть только у структуры или объекта, получено значение типа {}", field, type_name(&other)),
+        )
+        .with_note(format!("значение: {}", repr_value(&other)))),
+    }
+}
+
+// Номер поля по имени; если такого нет — ошибка с похожими именами
+fn field_index(ty: &StructType, name: &str, span: Span) -> Result<usize, RuntimeError> {
+    if let Some(index) = ty.field_index(name) {
+        return Ok(index);
+    }
+    if ty.method(name).is_some() {
+        return Err(error_at(span, codes::UNKNOWN_FIELD, format!("'{}' — метод класса '{}', а не поле", name, ty.name))
+            .with_note(format!("чтобы вызвать метод, добавьте скобки: {}()", name)));
+    }
+    let methods = ty.method_names();
+    let err = error_at(span, codes::UNKNOWN_FIELD, format!("У {} '{}' нет поля '{}'", ty.kind(), ty.name, name));
+    let err = match suggest::closest(name, ty.field_names().chain(methods.iter().copied())) {
+        Some(candidate) => err.with_note(suggest::did_you_mean(candidate)),
+        None => err,
+    };
+    let err = err.with_note(format!("поля {} {}: {}", ty.kind(), ty.name, ty.field_names().collect::<Vec<_>>().join(", ")));
+    if methods.is_empty() {
+        return Err(err);
+    }
+    Err(err.with_note(format!("методы класса {}: {}", ty.name, methods.join(", "))))
+}
+
+fn unknown_method(ty: &StructType, name: &str, span: Span) -> RuntimeError {
+    let methods = ty.method_names();
+    let err = error_at(span, codes::UNKNOWN_FIELD, format!("У класса '{}' нет метода '{}'", ty.name, name));
+    let err = match suggest::closest(name, methods.iter().copied()) {
+        Some(candidate) => err.with_note(suggest::did_you_mean(candidate)),
+        None => err,
+    };
+    if methods.is_empty() {
+        return err.with_note(format!("у класса {} нет методов", ty.name));
+    }
+    err.with_note(format!("методы класса {}: {}", ty.name, methods.join(", ")))
+}
+
+// Новая структура; значения полей проверяются по аннотациям, `spans` — где каждое записано
+fn new_structure(ty: Rc<StructType>, fields: Vec<Value>, spans: &[Span]) -> Result<Value, RuntimeError> {
+    for ((field, val), span) in ty.fields.iter().zip(&fields).zip(spans) {
+        if let Some(annotation) = &field.ty {
+            check_type(val, annotation, *span, || format!("Поле '{}' структуры '{}'", field.name, ty.name))?;
+        }
+    }
+    Ok(Value::Структура(Rc::new(RefCell::new(Structure { ty, fields }))))
+}
+
+// Подходит ли значение под образец; имена из образца попадают в `bindings`
+fn match_pattern(pattern: &Pattern, val: &Value, bindings: &mut Vec<(String, Value)>) -> Result<bool, RuntimeError> {
+    let matched = match (&pattern.kind, val) {
+        (PatternKind::Переменная(name), _) => {
+            if name != "_" {
+                bindings.push((name.clone(), val.clone()));
+            }
+            true
+        }
+        (PatternKind::Число(expected), Value::Число(n)) => n == expected,
+        (PatternKind::Строка(expected), Value::Строка(s)) => s == expected,
+        (PatternKind::Булево(expected), Value::Булево(b)) => b == expected,
+        (PatternKind::Диапазон(from, to), Value::Число(n)) => from <= n && n <= to,
+        (PatternKind::Список(patterns), Value::Список(items)) => {
+            let items = items.borrow().clone();
+            if items.len() != patterns.len() {
+                return Ok(false);
+            }
+            for (pattern, item) in patterns.iter().zip(&items) {
+                if !match_pattern(pattern, item, bindings)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        (PatternKind::Структура { имя, поля }, Value::Структура(structure)) => {
+            let structure = structure.borrow();
+            if !structure.ty.is_a(имя) {
+                return Ok(false);
+            }
+            for field in поля {
+                let index = field_index(&structure.ty, &field.name, field.span)?;
+                if !match_pattern(&field.pattern, &structure.fields[index], bindings)? {
+                    return Ok(false);
+                }
+            }
+            true
         }
+        _ => false,
+    };
+    Ok(matched)
+}
+
+fn as_dict(val: &Value, span: Span, function: &str) -> Result<Rc<RefCell<Entries>>, RuntimeError> {
+    match val {
+        Value::Словарь(entries) => Ok(entries.clone()),
+        other => Err(error_at(
+            span,
+            codes::ARGUMENT_TYPE,
+            format!("Первый аргумент {} должен быть словарём, получено значение типа {}", function, type_name(other)),
+        )),
     }
 }
 
+// Значение слева от `[индекс]` не список и не словарь
+fn not_indexable(val: &Value, span: Span) -> RuntimeError {
+    error_at(
+        span,
+        codes::RUNTIME_TYPE_MISMATCH,
+        format!("Обращаться по индексу можно только к списку или словарю, получено значение типа {}", type_name(val)),
+    )
+    .with_note(format!("значение: {}", repr_value(val)))
+}
+
+fn dict_key(val: &Value, span: Span) -> Result<Key, RuntimeError> {
+    match val {
+        Value::Число(n) => Ok(Key::Число(*n)),
+        Value::Строка(s) => Ok(Key::Строка(s.clone())),
+        other => Err(error_at(
+            span,
+            codes::ARGUMENT_TYPE,
+            format!("Ключ словаря должен быть строкой или числом, получено значение типа {}", type_name(other)),
+        )),
+    }
+}
+
+// Ошибка об отсутствующем ключе с подсказкой: похожие ключи или все, какие есть
+fn missing_key(entries: &[(Key, Value)], key: &Key, span: Span) -> RuntimeError {
+    const SHOWN_KEYS: usize = 5;
+    
+    let err = error_at(
+        span,
+        codes::MISSING_KEY,
+        format!("Ключ {} не найден в словаре", repr_value(&key.to_value())),
+    );
+    if entries.is_empty() {
+        return err.with_note("словарь пуст");
+    }
+    
+    let names: Vec<String> = entries.iter().map(|(k, _)| repr_value(&k.to_value())).collect();
+    let similar = match key {
+        Key::Строка(s) => suggest::similar(
+            s,
+            entries.iter().filter_map(|(k, _)| match k {
+                Key::Строка(candidate) => Some(candidate.as_str()),
+                Key::Число(_) => None,
+            }),
+            SHOWN_KEYS,
+        ),
+        Key::Число(_) => Vec::new(),
+    };
+    if !similar.is_empty() {
+        let similar: Vec<String> = similar.iter().map(|s| format!("\"{}\"", s)).collect();
+        return err.with_note(format!("похожие ключи: {}", similar.join(", ")));
+    }
+    
+    let mut note = format!("ключи словаря: {}", names[..names.len().min(SHOWN_KEYS)].join(", "));
+    if names.len() > SHOWN_KEYS {
+        note.push_str(&format!(" и ещё {}", names.len() - SHOWN_KEYS));
+    }
+    err.with_note(note)
+}
+
+// Номер элемента списка длины `len`; `allow_end` разрешает индекс сразу за последним элементом
+fn list_index(val: &Value, len: usize, span: Span, allow_end: bool) -> Result<usize, RuntimeError> {
+    let n = match val {
+        Value::Число(n) => *n,
+        other => {
+            return Err(error_at(
+                span,
+                codes::ARGUMENT_TYPE,
+                format!("Индекс должен быть числом, получено значение типа {}", type_name(other)),
+            ));
+        }
+    };
+    if n.fract() != 0.0 {
+        return Err(error_at(span, codes::ARGUMENT_TYPE, format!("Индекс должен быть целым числом, получено {}", n)));
+    }
+    
+    let limit = if allow_end { len + 1 } else { len };
+    if n < 0.0 || n >= limit as f64 {
+        let note = match limit {
+            0 => "список пуст".to_string(),
+            _ => format!("допустимые индексы: от 0 до {}", limit - 1),
+        };
+        return Err(error_at(
+            span,
+            codes::INDEX_OUT_OF_BOUNDS,
+            format!("Индекс {} вне границ списка длины {}", n, len),
+        )
+        .with_note(note));
+    }
+    Ok(n as usize)
+}
+
+// Элемент списка, словаря или структуры: строки в кавычках. Значение,
+// которое содержит само себя, внутри себя печатается как `[...]` или `{...}`.
+// `open` — коллекции, которые сейчас печатаются.
+fn format_nested(val: &Value, open: &mut Vec<*const ()>) -> String {
+    let ptr = match val {
+        Value::Список(items) => Rc::as_ptr(items) as *const (),
+        Value::Словарь(entries) => Rc::as_ptr(entries) as *const (),
+        Value::Структура(structure) => Rc::as_ptr(structure) as *const (),
+        other => return repr_value(other),
+    };
+    if open.contains(&ptr) {
+        return match val {
+            Value::Список(_) => "[...]".to_string(),
+            Value::Структура(structure) => format!("{} {{...}}", structure.borrow().ty.name),
+            _ => "{...}".to_string(),
+        };
+    }
+    
+    open.push(ptr);
+    let text = match val {
+        Value::Список(items) => {
+            let parts: Vec<String> = items.borrow().iter().map(|item| format_nested(item, open)).collect();
+            format!("[{}]", parts.join(", "))
+        }
+        Value::Словарь(entries) => {
+            let parts: Vec<String> = entries
+                .borrow()
+                .iter()
+                .map(|(key, item)| format!("{}: {}", repr_value(&key.to_value()), format_nested(item, open)))
+                .collect();
+            format!("{{{}}}", parts.join(", "))
+        }
+        Value::Структура(structure) => {
+            let structure = structure.borrow();
+            let parts: Vec<String> = structure
+                .ty
+                .field_names()
+                .zip(&structure.fields)
+                .map(|(name, item)| format!("{}: {}", name, format_nested(item, open)))
+                .collect();
+            format!("{} {{ {} }}", structure.ty.name, parts.join(", "))
+        }
+        _ => unreachable!(),
+    };
+    open.pop();
+    text
+}
+
 fn format_value(val: &Value) -> String {
     match val {
         Value::Число(n) => n.to_string(),
         Value::Строка(s) => s.clone(),
         Value::Булево(b) => if *b { "истина" } else { "ложь" }.to_string(),
-        Value::Функция { .. } => "<функция>".to_string(),
+        Value::Список(_) | Value::Словарь(_) | Value::Структура(_) => format_nested(val, &mut Vec::new()),
+        Value::Функция(function) => match &function.name {
+            Some(name) => format!("<функция {}>", name),
+            None => "<функция>".to_string(),
+        },
         Value::Пусто => "пусто".to_string(),
     }
-}
\ No newline at end of file
+}
+
+// Значение в том виде, в каком оно записывается в коде: строки в кавычках
+fn repr_value(val: &Value) -> String {
+    match val {
+        Value::Строка(s) => format!("\"{}\"", s),
+        other => format_value(other),
+    }
+}
+
+/// Тип значения, как он пишется в аннотации.
+pub fn value_type(val: &Value) -> Type {
+    match val {
+        Value::Число(_) => Type::Число,
+        Value::Строка(_) => Type::Строка,
+        Value::Булево(_) => Type::Булево,
+        Value::Список(_) => Type::Список,
+        Value::Словарь(_) => Type::Словарь,
+        Value::Функция(_) => Type::Функция,
+        Value::Структура(structure) if structure.borrow().ty.class.is_some() => Type::Объект,
+        Value::Структура(_) => Type::Структура,
+        Value::Пусто => Type::Пусто,
+    }
+}
+
+fn type_name(val: &Value) -> &'static str {
+    value_type(val).name()
+}
+
+// Проверка аннотации во время выполнения; `what` — чьё значение проверяется
+fn check_type(
+    val: &Value,
+    expected: &TypeAnnotation,
+    span: Span,
+    what: impl FnOnce() -> String,
+) -> Result<(), RuntimeError> {
+    if value_type(val) == expected.ty {
+        return Ok(());
+    }
+    Err(error_at(
+        span,
+        codes::RUNTIME_TYPE_MISMATCH,
+        format!("{}: ожидается тип {}, получено значение типа {}", what(), expected.ty, type_name(val)),
+    )
+    .with_note(format!("значение: {}", repr_value(val))))
+}
+
+// Истина или ложь; `what` — начало сообщения о том, что проверяется
+fn expect_boolean(val: Value, span: Span, what: impl FnOnce() -> String) -> Result<bool, RuntimeError> {
+    match val {
+        Value::Булево(b) => Ok(b),
+        other => Err(error_at(
+            span,
+            codes::RUNTIME_TYPE_MISMATCH,
+            format!("{}, получено значение типа {}", what(), type_name(&other)),
+        )
+        .with_note(format!("значение: {}", repr_value(&other)))),
+    }
+}
+
+fn error_at(span: Span, code: &'static str, message: impl Into<String>) -> RuntimeError {
+    RuntimeError { code: code.into(), message: message.into(), span, trace: Vec::new(), notes: Vec::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+    use crate::compiler::parser::Parser;
+    use std::sync::{Arc, Mutex};
+    
+    // Выполняет программу без статических проверок: вывод и итог выполнения
+    fn run(code: &str) -> (String, Result<(), RuntimeError>) {
+        let (tokens, lex_errors) = Lexer::new(code.to_string()).tokenize();
+        assert!(lex_errors.is_empty(), "лексические ошибки: {:?}", lex_errors);
+        let (program, parse_errors) = Parser::new(tokens).parse();
+        assert!(parse_errors.is_empty(), "синтаксические ошибки: {:?}", parse_errors);
+        
+        let output = Arc::new(Mutex::new(String::new()));
+        let sink = output.clone();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output_handler(Box::new(move |text| {
+            let mut out = sink.lock().unwrap();
+            out.push_str(&text);
+            out.push('\n');
+        }));
+        let result = interpreter.execute(program);
+        let output = output.lock().unwrap().clone();
+        (output, result)
+    }
+    
+    fn run_ok(code: &str) -> String {
+        let (output, result) = run(code);
+        if let Err(err) = result {
+            panic!("ошибка выполнения: {}", err.message);
+        }
+        output
+    }
+    
+    fn run_err(code: &str) -> RuntimeError {
+        match run(code) {
+            (_, Err(err)) => err,
+            (output, Ok(())) => panic!("ожидалась ошибка, вывод: {}", output),
+        }
+    }
+    
+    #[test]
+    fn logical_operators_short_circuit() {
+        let code = "функция громко(x) { печать(x); вернуть x; }
+                    печать(ложь и громко(истина));
+                    печать(истина или громко(ложь));
+                    печать(не ложь и громко(истина));";
+        assert_eq!(run_ok(code), "ложь\nистина\nистина\nистина\n");
+    }
+    
+    #[test]
+    fn condition_must_be_boolean() {
+        let err = run_err("пусть x = 1; если (x) { печать(x); }");
+        assert_eq!(&*err.code, codes::RUNTIME_TYPE_MISMATCH);
+        assert_eq!(err.message, "Условие 'если' должно иметь тип булево, получено значение типа число");
+        
+        let err = run_err("пусть x = \"да\"; пока (x) { прервать; }");
+        assert_eq!(err.message, "Условие 'пока' должно иметь тип булево, получено значение типа строка");
+    }
+    
+    #[test]
+    fn logical_operands_must_be_boolean() {
+        let err = run_err("печать(1 и истина);");
+        assert_eq!(&*err.code, codes::RUNTIME_TYPE_MISMATCH);
+        assert_eq!(err.message, "Операнд 'и' должен иметь тип булево, получено значение типа число");
+        
+        let err = run_err("печать(не \"да\");");
+        assert_eq!(&*err.code, codes::RUNTIME_TYPE_MISMATCH);
+    }
+    
+    #[test]
+    fn counted_loop_with_step() {
+        assert_eq!(run_ok("для и от 1 до 2 шаг 0.5 { печать(и); }"), "1\n1.5\n2\n");
+        assert_eq!(run_ok("для и от 3 до 1 шаг -1 { печать(и); }"), "3\n2\n1\n");
+        assert_eq!(run_ok("для и от 3 до 1 { печать(и); }"), "");
+    }
+    
+    #[test]
+    fn counted_loop_rejects_infinite_bounds() {
+        let err = run_err("для и от 0 до 10^400 { прервать; }");
+        assert_eq!(&*err.code, codes::DOMAIN_ERROR);
+        assert_eq!(err.span.column, 15);
+        
+        let err = run_err("для и от 0 до 10 шаг 10^400 - 10^400 { прервать; }");
+        assert_eq!(&*err.code, codes::DOMAIN_ERROR);
+    }
+    
+    #[test]
+    fn counted_loop_rejects_too_many_iterations() {
+        for code in [
+            "для и от 0 до 10^20 { прервать; }",
+            "для и от 0 до 10 шаг 0.1^300 { прервать; }",
+            "для и от -10^308 до 10^308 { прервать; }",
+        ] {
+            assert_eq!(&*run_err(code).code, codes::DOMAIN_ERROR, "{}", code);
+        }
+    }
+    
+    #[test]
+    fn runtime_error_becomes_a_diagnostic_with_position() {
+        let err = run_err("пусть x = 1;\nпечать(x / 0);");
+        let diagnostic = Diagnostic::from(err);
+        assert_eq!(diagnostic.code, codes::DIVISION_BY_ZERO);
+        assert_eq!(diagnostic.stage, Stage::Runtime);
+        let span = diagnostic.span.unwrap();
+        assert_eq!((span.line, span.column), (2, 8));
+    }
+    
+    #[test]
+    fn runtime_error_has_the_call_stack() {
+        let err = run_err("функция внутр(x) { вернуть 1 / x; }\nфункция внеш() { вернуть внутр(0); }\nвнеш();");
+        let diagnostic = Diagnostic::from(err);
+        let lines: Vec<u32> = diagnostic.trace.iter().map(|frame| frame.span.line).collect();
+        assert_eq!(lines, [3, 2]);
+    }
+    
+    #[test]
+    fn arithmetic_operators() {
+        // Степень правоассоциативна и связывает сильнее унарного минуса
+        assert_eq!(run_ok("печать(7 % 3, 7 mod 3, 2 ^ 3 ^ 2, -2 ^ 2, 7 div 2);"), "1 1 512 -4 3\n");
+        assert_eq!(&*run_err("печать(7 div 0);").code, codes::DIVISION_BY_ZERO);
+    }
+    
+    #[test]
+    fn break_and_continue() {
+        let code = "для и от 1 до 10 { если (и == 2) { продолжить; } если (и == 4) { прервать; } печать(и); }";
+        assert_eq!(run_ok(code), "1\n3\n");
+    }
+    
+    #[test]
+    fn lists() {
+        let code = "пусть с = [1, 2];
+                    добавить(с, 3);
+                    с[0] = 10;
+                    печать(длина(с), с[0], с);";
+        assert_eq!(run_ok(code), "3 10 [10, 2, 3]\n");
+        assert_eq!(&*run_err("пусть с = [1]; печать(с[1]);").code, codes::INDEX_OUT_OF_BOUNDS);
+    }
+    
+    #[test]
+    fn dictionaries() {
+        let code = "пусть д = {\"а\": 1};
+                    д[\"б\"] = [2];
+                    печать(ключи(д), д[\"б\"], содержит(д, \"в\"));";
+        assert_eq!(run_ok(code), "[\"а\", \"б\"] [2] ложь\n");
+        assert_eq!(&*run_err("пусть д = {}; печать(д[\"к\"]);").code, codes::MISSING_KEY);
+    }
+    
+    #[test]
+    fn closures_capture_their_scope() {
+        let code = "функция счётчик() { пусть н = 0; вернуть фн() { н = н + 1; вернуть н; }; }
+                    пусть с = счётчик();
+                    с(); с();
+                    печать(с(), счётчик()());";
+        assert_eq!(run_ok(code), "3 1\n");
+    }
+    
+    #[test]
+    fn block_variables_are_local() {
+        let code = "пусть x = 1; если (истина) { пусть x = 2; печать(x); } печать(x);";
+        assert_eq!(run_ok(code), "2\n1\n");
+    }
+    
+    #[test]
+    fn structures() {
+        let code = "структура Точка { x, y }
+                    пусть т = Точка { x: 1, y: 2 };
+                    т.x = 5;
+                    печать(т.x + т.y);";
+        assert_eq!(run_ok(code), "7\n");
+        assert_eq!(&*run_err("структура Т { x } пусть т = Т { x: 1 }; печать(т.y);").code, codes::UNKNOWN_FIELD);
+    }
+    
+    #[test]
+    fn classes() {
+        let code = "класс Животное {
+                        имя = \"\";
+                        функция конструктор(имя) { этот.имя = имя; }
+                        функция голос() { вернуть \"...\"; }
+                        функция представиться() { вернуть этот.имя + \": \" + этот.голос(); }
+                    }
+                    класс Кошка наследует Животное {
+                        функция голос() { вернуть \"мяу\"; }
+                    }
+                    печать(Кошка(\"Мурка\").представиться());";
+        assert_eq!(run_ok(code), "Мурка: мяу\n");
+    }
+    
+    #[test]
+    fn match_statement_picks_the_first_matching_case() {
+        let code = "функция вид(x) {
+                        выбор (x) {
+                            случай 0: вернуть \"ноль\";
+                            случай от 1 до 9: вернуть \"цифра\";
+                            случай [a, b]: вернуть \"пара {a} {b}\";
+                            иначе: вернуть \"другое\";
+                        }
+                    }
+                    печать(вид(0), вид(5), вид([1, 2]), вид(\"x\"));";
+        assert_eq!(run_ok(code), "ноль цифра пара 1 2 другое\n");
+    }
+    
+    #[test]
+    fn exceptions_are_caught_and_finally_runs() {
+        let code = "попытка { печать(1 / 0); } перехват (о) { печать(о.код); } наконец { печать(\"н\"); }
+                    попытка { бросить \"своя\"; } перехват (о) { печать(о.сообщение, о.строка); }";
+        assert_eq!(run_ok(code), "R0005\nн\nсвоя 2\n");
+        
+        let err = run_err("попытка { бросить \"вверх\"; } наконец { печать(1); }");
+        assert_eq!(&*err.code, codes::USER_ERROR);
+        assert_eq!(err.message, "вверх");
+    }
+    
+    #[test]
+    fn string_interpolation() {
+        let code = "пусть имя = \"Аня\"; пусть с = [1, 2]; печать(\"{имя}: {длина(с) * 2}, {{x}}\");";
+        assert_eq!(run_ok(code), "Аня: 4, {x}\n");
+    }
+    
+    #[test]
+    fn parameter_shadows_a_global_function() {
+        let code = "функция f(x) { вернуть x + 1; }
+                    функция применить(f, x) { вернуть f(x); }
+                    печать(применить(фн(x) { вернуть x * 10; }, 2), f(2));
+                    функция g(длина) { вернуть длина(1, 2); }
+                    печать(g(фн(a, b) { вернуть a + b; }));";
+        assert_eq!(run_ok(code), "20 3\n3\n");
+    }
+    
+    #[test]
+    fn nested_function_is_not_visible_outside() {
+        let code = "функция внеш() { функция внутр() { вернуть 1; } вернуть внутр(); }
+                    печать(внеш());
+                    печать(внутр());";
+        let (output, result) = run(code);
+        assert_eq!(output, "1\n");
+        assert_eq!(&*result.unwrap_err().code, codes::UNDEFINED_FUNCTION);
+    }
+    
+    #[test]
+    fn functions_can_be_called_above_their_declaration() {
+        let code = "печать(f());
+                    функция f() { вернуть г(); }
+                    если (истина) { печать(г()); функция г() { вернуть 2; } }
+                    функция г() { вернуть 1; }";
+        assert_eq!(run_ok(code), "1\n2\n");
+    }
+}
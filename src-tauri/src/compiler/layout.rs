@@ -0,0 +1,128 @@
+//! Поиск имён, набранных не в той раскладке клавиатуры: `gecnm` вместо `пусть`.
+
+use crate::compiler::diagnostics::{codes, Diagnostic, Stage};
+use crate::compiler::interpreter::BUILTINS;
+use crate::compiler::lexer::{SpannedToken, Token, KEYWORDS};
+use std::collections::HashSet;
+
+// Буквы, стоящие на одной клавише в раскладках QWERTY и ЙЦУКЕН
+const LAYOUT: &[(char, char)] = &[
+    ('q', 'й'), ('w', 'ц'), ('e', 'у'), ('r', 'к'), ('t', 'е'), ('y', 'н'),
+    ('u', 'г'), ('i', 'ш'), ('o', 'щ'), ('p', 'з'), ('a', 'ф'), ('s', 'ы'),
+    ('d', 'в'), ('f', 'а'), ('g', 'п'), ('h', 'р'), ('j', 'о'), ('k', 'л'),
+    ('l', 'д'), ('z', 'я'), ('x', 'ч'), ('c', 'с'), ('v', 'м'), ('b', 'и'),
+    ('n', 'т'), ('m', 'ь'),
+];
+
+fn to_cyrillic(ch: char) -> Option<char> {
+    let lower = ch.to_lowercase().next()?;
+    let (_, cyr) = LAYOUT.iter().find(|(lat, _)| *lat == lower)?;
+    Some(if ch.is_uppercase() { cyr.to_uppercase().next()? } else { *cyr })
+}
+
+fn to_latin(ch: char) -> Option<char> {
+    let lower = ch.to_lowercase().next()?;
+    let (lat, _) = LAYOUT.iter().find(|(_, cyr)| *cyr == lower)?;
+    Some(if ch.is_uppercase() { lat.to_ascii_uppercase() } else { *lat })
+}
+
+// Слово целиком в другой раскладке; `None`, если какую-то букву перевести нельзя
+fn convert(word: &str, map: fn(char) -> Option<char>) -> Option<String> {
+    word.chars().map(map).collect()
+}
+
+/// Проверяет имена в потоке токенов. Латинское имя, которое в русской
+/// раскладке даёт ключевое слово, встроенную функцию или имя из этой же
+/// программы, и наоборот, получает предупреждение с исправлением.
+pub fn check_layout(tokens: &[SpannedToken]) -> Vec<Diagnostic> {
+    let mut cyrillic_names: HashSet<&str> = KEYWORDS.iter().map(|(word, _)| *word).collect();
+    cyrillic_names.extend(BUILTINS.iter().copied());
+    let mut latin_names = HashSet::new();
+    // Имена, которые программа объявляет сама, не исправляем
+    let mut declared = HashSet::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        if let Token::Идентификатор(name) = &token.token {
+            if name.chars().any(|ch| ch.is_ascii_alphabetic()) {
+                latin_names.insert(name.as_str());
+            } else {
+                cyrillic_names.insert(name.as_str());
+            }
+            let after_declaration = i > 0
+                && matches!(
+                    tokens[i - 1].token,
+                    Token::Пусть | Token::Функция | Token::Структура | Token::Класс | Token::Для
+                );
+            if after_declaration {
+                declared.insert(name.as_str());
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for token in tokens {
+        let Token::Идентификатор(name) = &token.token else {
+            continue;
+        };
+        // Однобуквенные имена в обеих раскладках слишком часто совпадают случайно
+        if declared.contains(name.as_str()) || name.chars().count() < 2 {
+            continue;
+        }
+
+        let english = convert(name, to_cyrillic)
+            .filter(|word| cyrillic_names.contains(word.as_str()))
+            .map(|word| ("английская", word));
+        let russian = || {
+            convert(name, to_latin)
+                .filter(|word| latin_names.contains(word.as_str()))
+                .map(|word| ("русская", word))
+        };
+
+        if let Some((layout, word)) = english.or_else(russian) {
+            diagnostics.push(
+                Diagnostic::warning(
+                    Stage::Lexing,
+                    codes::WRONG_LAYOUT,
+                    format!("Похоже, включена {} раскладка: {} → {}", layout, name, word),
+                )
+                .with_span(token.span)
+                .with_fix(format!("Заменить на '{}'", word), token.span, word),
+            );
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+
+    fn warnings(code: &str) -> Vec<Diagnostic> {
+        let (tokens, _) = Lexer::new(code.to_string()).tokenize();
+        check_layout(&tokens)
+    }
+
+    #[test]
+    fn english_layout_is_converted_to_a_known_word() {
+        let found = warnings("gtxfnm(1);");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, codes::WRONG_LAYOUT);
+        assert_eq!(found[0].message, "Похоже, включена английская раскладка: gtxfnm → печать");
+        assert_eq!(found[0].fixes[0].edits[0].replacement, "печать");
+    }
+
+    #[test]
+    fn russian_layout_is_converted_to_a_name_of_the_program() {
+        let found = warnings("пусть name = 1; печать(тфьу);");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message, "Похоже, включена русская раскладка: тфьу → name");
+    }
+
+    #[test]
+    fn declared_and_short_names_are_left_alone() {
+        assert!(warnings("пусть gtxfnm = 1; gtxfnm = 2;").is_empty());
+        assert!(warnings("пусть x = 1; печать(x);").is_empty());
+    }
+}
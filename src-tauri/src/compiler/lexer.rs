@@ -1,3 +1,49 @@
+use crate::compiler::diagnostics::{codes, Diagnostic, Stage};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Номер главного файла программы; подключённые файлы получают следующие.
+pub const MAIN_FILE: u32 = 0;
+
+/// Участок исходного текста: байтовые смещения и позиции (строки и столбцы с 1).
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Span {
+    // Номер файла в списке файлов программы
+    pub file: u32,
+    pub start: u32,
+    pub end: u32,
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl Span {
+    /// Участок от начала `self` до конца `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            file: self.file,
+            start: self.start,
+            end: other.end,
+            line: self.line,
+            column: self.column,
+            end_line: other.end_line,
+            end_column: other.end_column,
+        }
+    }
+
+    /// Пустой участок в начале `self`: место, куда можно вставить текст.
+    pub fn start_point(self) -> Span {
+        Span { end: self.start, end_line: self.line, end_column: self.column, ..self }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "строка {}, столбец {}", self.line, self.column)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     // Ключевые слова
@@ -6,7 +52,20 @@ pub enum Token {
     Если,
     Иначе,
     Пока,
+    Для,
     Вернуть,
+    Прервать,
+    Продолжить,
+    Структура,
+    Класс,
+    Этот,
+    Выбор,
+    Случай,
+    Попытка,
+    Перехват,
+    Наконец,
+    Бросить,
+    Подключить,
     Истина,
     Ложь,
     
@@ -14,12 +73,16 @@ pub enum Token {
     Идентификатор(String),
     Число(f64),
     Строка(String),
+    // Строка с подстановками: `"x = {x}"`
+    Шаблон(Vec<StringPart>),
     
     // Операторы
     Плюс,           // +
     Минус,          // -
     Умножить,       // *
     Разделить,      // /
+    Остаток,        // %
+    Степень,        // ^, **
     Присвоить,      // =
     Равно,          // ==
     НеРавно,        // !=
@@ -27,40 +90,216 @@ pub enum Token {
     Меньше,         // 
     БольшеРавно,    // >=
     МеньшеРавно,    // <=
+    И,              // &&
+    Или,            // ||
+    Не,             // не, !
     
     // Разделители
     ЛевСкобка,      // (
     ПравСкобка,     // )
     ЛевФигСкобка,   // {
     ПравФигСкобка,  // }
+    ЛевКвСкобка,    // [
+    ПравКвСкобка,   // ]
     Запятая,        // ,
     ТочкаЗапятая,   // ;
+    Двоеточие,      // :
+    Точка,          // .
+    
+    // Нераспознанный фрагмент; сама ошибка уходит в список LexError
+    Ошибка,
     
     КонецФайла,
 }
 
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Пусть => write!(f, "ключевое слово 'пусть'"),
+            Token::Функция => write!(f, "ключевое слово 'функция'"),
+            Token::Если => write!(f, "ключевое слово 'если'"),
+            Token::Иначе => write!(f, "ключевое слово 'иначе'"),
+            Token::Пока => write!(f, "ключевое слово 'пока'"),
+            Token::Для => write!(f, "ключевое слово 'для'"),
+            Token::Вернуть => write!(f, "ключевое слово 'вернуть'"),
+            Token::Прервать => write!(f, "ключевое слово 'прервать'"),
+            Token::Продолжить => write!(f, "ключевое слово 'продолжить'"),
+            Token::Структура => write!(f, "ключевое слово 'структура'"),
+            Token::Класс => write!(f, "ключевое слово 'класс'"),
+            Token::Этот => write!(f, "ключевое слово 'этот'"),
+            Token::Выбор => write!(f, "ключевое слово 'выбор'"),
+            Token::Случай => write!(f, "ключевое слово 'случай'"),
+            Token::Попытка => write!(f, "ключевое слово 'попытка'"),
+            Token::Перехват => write!(f, "ключевое слово 'перехват'"),
+            Token::Наконец => write!(f, "ключевое слово 'наконец'"),
+            Token::Бросить => write!(f, "ключевое слово 'бросить'"),
+            Token::Подключить => write!(f, "ключевое слово 'подключить'"),
+            Token::Истина => write!(f, "значение 'истина'"),
+            Token::Ложь => write!(f, "значение 'ложь'"),
+            Token::Идентификатор(name) => write!(f, "имя '{}'", name),
+            Token::Число(n) => write!(f, "число {}", n),
+            Token::Строка(s) => write!(f, "строка \"{}\"", s),
+            Token::Шаблон(_) => write!(f, "строка с подстановками"),
+            Token::Плюс => write!(f, "знак '+'"),
+            Token::Минус => write!(f, "знак '-'"),
+            Token::Умножить => write!(f, "знак '*'"),
+            Token::Разделить => write!(f, "знак '/'"),
+            Token::Остаток => write!(f, "знак '%'"),
+            Token::Степень => write!(f, "знак степени '^'"),
+            Token::Присвоить => write!(f, "знак присваивания '='"),
+            Token::Равно => write!(f, "сравнение '=='"),
+            Token::НеРавно => write!(f, "сравнение '!='"),
+            Token::Больше => write!(f, "сравнение '>'"),
+            Token::Меньше => write!(f, "сравнение '<'"),
+            Token::БольшеРавно => write!(f, "сравнение '>='"),
+            Token::МеньшеРавно => write!(f, "сравнение '<='"),
+            Token::И => write!(f, "оператор '&&'"),
+            Token::Или => write!(f, "оператор '||'"),
+            Token::Не => write!(f, "отрицание 'не'"),
+            Token::ЛевСкобка => write!(f, "открывающая скобка '('"),
+            Token::ПравСкобка => write!(f, "закрывающая скобка ')'"),
+            Token::ЛевФигСкобка => write!(f, "открывающая фигурная скобка '{{'"),
+            Token::ПравФигСкобка => write!(f, "закрывающая фигурная скобка '}}'"),
+            Token::ЛевКвСкобка => write!(f, "открывающая квадратная скобка '['"),
+            Token::ПравКвСкобка => write!(f, "закрывающая квадратная скобка ']'"),
+            Token::Запятая => write!(f, "запятая ','"),
+            Token::ТочкаЗапятая => write!(f, "точка с запятой ';'"),
+            Token::Двоеточие => write!(f, "двоеточие ':'"),
+            Token::Точка => write!(f, "точка '.'"),
+            Token::Ошибка => write!(f, "ошибочный фрагмент"),
+            Token::КонецФайла => write!(f, "конец файла"),
+        }
+    }
+}
+
+/// Таблица ключевых слов языка.
+pub const KEYWORDS: &[(&str, Token)] = &[
+    ("пусть", Token::Пусть),
+    ("функция", Token::Функция),
+    ("фн", Token::Функция),
+    ("если", Token::Если),
+    ("иначе", Token::Иначе),
+    ("пока", Token::Пока),
+    ("для", Token::Для),
+    ("вернуть", Token::Вернуть),
+    ("прервать", Token::Прервать),
+    ("продолжить", Token::Продолжить),
+    ("структура", Token::Структура),
+    ("класс", Token::Класс),
+    ("этот", Token::Этот),
+    ("выбор", Token::Выбор),
+    ("случай", Token::Случай),
+    ("попытка", Token::Попытка),
+    ("перехват", Token::Перехват),
+    ("наконец", Token::Наконец),
+    ("бросить", Token::Бросить),
+    ("подключить", Token::Подключить),
+    ("истина", Token::Истина),
+    ("ложь", Token::Ложь),
+    ("не", Token::Не),
+];
+
+pub fn keyword(word: &str) -> Option<Token> {
+    KEYWORDS
+        .iter()
+        .find(|(w, _)| *w == word)
+        .map(|(_, token)| token.clone())
+}
+
+/// Часть строки с подстановками.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Текст(String),
+    // Токены выражения вместе с закрывающей '}'
+    Подстановка(Vec<SpannedToken>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(err: LexError) -> Self {
+        Diagnostic::error(Stage::Lexing, err.code, err.message).with_span(err.span)
+    }
+}
+
 pub struct Lexer {
+    file: u32,
     input: Vec<char>,
     position: usize,
     current_char: Option<char>,
+    offset: u32,
+    line: u32,
+    column: u32,
+    // Ошибки внутри подстановок: строка с ними читается дальше, так что
+    // `next_token` возвращает её токен, а ошибки забирает `tokenize`
+    errors: Vec<LexError>,
+}
+
+// Место в тексте, куда лексер может вернуться
+#[derive(Clone, Copy)]
+struct Checkpoint {
+    position: usize,
+    mark: Span,
 }
 
 impl Lexer {
     pub fn new(input: String) -> Self {
         let chars: Vec<char> = input.chars().collect();
-        let current = chars.get(0).copied();
+        let current = chars.first().copied();
         Lexer {
+            file: MAIN_FILE,
             input: chars,
             position: 0,
             current_char: current,
+            offset: 0,
+            line: 1,
+            column: 1,
+            errors: Vec::new(),
         }
     }
     
+    /// Лексер для подключённого файла с номером `file`.
+    pub fn with_file(input: String, file: u32) -> Self {
+        Lexer { file, ..Self::new(input) }
+    }
+    
     fn advance(&mut self) {
+        if let Some(ch) = self.current_char {
+            self.offset += ch.len_utf8() as u32;
+            if ch == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
         self.position += 1;
         self.current_char = self.input.get(self.position).copied();
     }
     
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { position: self.position, mark: self.mark() }
+    }
+    
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.position = checkpoint.position;
+        self.current_char = self.input.get(self.position).copied();
+        self.offset = checkpoint.mark.start;
+        self.line = checkpoint.mark.line;
+        self.column = checkpoint.mark.column;
+    }
+    
     fn peek(&self, offset: usize) -> Option<char> {
         self.input.get(self.position + offset).copied()
     }
@@ -75,7 +314,7 @@ impl Lexer {
         }
     }
     
-    fn skip_comment(&mut self) {
+    fn skip_comment(&mut self) -> Result<(), LexError> {
         // Однострочный комментарий //
         if self.current_char == Some('/') && self.peek(1) == Some('/') {
             while self.current_char.is_some() && self.current_char != Some('\n') {
@@ -85,6 +324,7 @@ impl Lexer {
         
         // Многострочный комментарий /* */
         if self.current_char == Some('/') && self.peek(1) == Some('*') {
+            let start = self.mark();
             self.advance(); // /
             self.advance(); // *
             
@@ -92,14 +332,22 @@ impl Lexer {
                 if self.current_char == Some('*') && self.peek(1) == Some('/') {
                     self.advance(); // *
                     self.advance(); // /
-                    break;
+                    return Ok(());
                 }
                 self.advance();
             }
+            
+            return Err(LexError {
+                code: codes::UNTERMINATED_COMMENT,
+                message: "Незакрытый комментарий: не найдено '*/'".to_string(),
+                span: start.to(self.mark()),
+            });
         }
+        
+        Ok(())
     }
     
-    fn read_number(&mut self) -> f64 {
+    fn read_number(&mut self, start: Span) -> Result<f64, LexError> {
         let mut num_str = String::new();
         
         while let Some(ch) = self.current_char {
@@ -111,7 +359,11 @@ impl Lexer {
             }
         }
         
-        num_str.parse().unwrap_or(0.0)
+        num_str.parse().map_err(|_| LexError {
+            code: codes::INVALID_NUMBER,
+            message: format!("Неверная запись числа '{}'", num_str),
+            span: start.to(self.mark()),
+        })
     }
     
     fn read_identifier(&mut self) -> String {
@@ -129,127 +381,363 @@ impl Lexer {
         id
     }
     
-    fn read_string(&mut self) -> String {
+    // Строка в кавычках. `{выражение}` внутри — подстановка, её токены
+    // читаются тут же, поэтому у них верные позиции; `{{` и `}}` — сами скобки.
+    fn read_string(&mut self, start: Span) -> Result<Token, LexError> {
+        let mut parts = Vec::new();
         let mut string = String::new();
         self.advance(); // Пропустить открывающую кавычку
         
         while let Some(ch) = self.current_char {
-            if ch == '"' {
-                self.advance();
-                break;
+            match ch {
+                '"' => {
+                    self.advance();
+                    if parts.is_empty() {
+                        return Ok(Token::Строка(string));
+                    }
+                    if !string.is_empty() {
+                        parts.push(StringPart::Текст(string));
+                    }
+                    return Ok(Token::Шаблон(parts));
+                }
+                '{' | '}' if self.peek(1) == Some(ch) => {
+                    string.push(ch);
+                    self.advance();
+                    self.advance();
+                }
+                '{' => {
+                    let open = self.mark();
+                    self.advance();
+                    if !string.is_empty() {
+                        parts.push(StringPart::Текст(std::mem::take(&mut string)));
+                    }
+                    let open = open.to(self.mark());
+                    parts.push(StringPart::Подстановка(self.read_substitution(open)));
+                }
+                _ => {
+                    string.push(ch);
+                    self.advance();
+                }
             }
-            string.push(ch);
-            self.advance();
         }
         
-        string
+        Err(LexError {
+            code: codes::UNTERMINATED_STRING,
+            message: "Незакрытая строка: не найдена закрывающая кавычка".to_string(),
+            span: start.to(self.mark()),
+        })
+    }
+    
+    // Токены подстановки до её закрывающей '}' включительно; '{' и '}'
+    // словаря внутри выражения учитываются. Ошибка в подстановке заменяет
+    // её целиком на токен ошибки, а строка после неё читается дальше.
+    fn read_substitution(&mut self, open: Span) -> Vec<SpannedToken> {
+        let mut tokens = Vec::new();
+        let reported = self.errors.len();
+        let mut depth = 0;
+        let mut line = open.end_line;
+        // Первая кавычка в подстановке: либо начало вложенной строки,
+        // либо конец внешней, если подстановку забыли закрыть
+        let mut quote = None;
+        let closing = loop {
+            let before = self.checkpoint();
+            self.skip_whitespace();
+            // Вне скобок подстановка не переносится на другую строку кода
+            let ended = self.current_char.is_none() || (depth == 0 && self.line > line);
+            if ended {
+                // Незакрытая подстановка кончается на кавычке, если та была,
+                // а иначе там, где кончилась строка кода
+                match quote {
+                    Some((at, count, errors)) => {
+                        self.restore(at);
+                        tokens.truncate(count);
+                        self.errors.truncate(errors);
+                    }
+                    None => self.restore(before),
+                }
+                break None;
+            }
+            if quote.is_none() && self.current_char == Some('"') {
+                quote = Some((self.checkpoint(), tokens.len(), self.errors.len()));
+            }
+            
+            let token = match self.next_token() {
+                Ok(token) => token,
+                Err(err) => {
+                    line = err.span.end_line;
+                    // Дальше до конца подстановки ошибки уже не сообщаются
+                    if self.errors.len() == reported {
+                        self.errors.push(err);
+                    }
+                    continue;
+                }
+            };
+            line = token.span.end_line;
+            match token.token {
+                Token::ЛевФигСкобка => depth += 1,
+                Token::ПравФигСкобка if depth == 0 => break Some(token),
+                Token::ПравФигСкобка => depth -= 1,
+                // Конец файла сразу за комментарием: дальше цикл его увидит
+                Token::КонецФайла => continue,
+                _ => {}
+            }
+            tokens.push(token);
+        };
+        
+        let closing = closing.unwrap_or_else(|| {
+            self.errors.push(LexError {
+                code: codes::UNTERMINATED_STRING,
+                message: "Незакрытая подстановка в строке: не найдена '}'".to_string(),
+                span: open,
+            });
+            SpannedToken { token: Token::ПравФигСкобка, span: open }
+        });
+        if let Some(err) = self.errors.get(reported) {
+            tokens = vec![SpannedToken { token: Token::Ошибка, span: err.span }];
+        }
+        tokens.push(closing);
+        tokens
     }
     
-    pub fn next_token(&mut self) -> Token {
+    fn mark(&self) -> Span {
+        Span {
+            file: self.file,
+            start: self.offset,
+            end: self.offset,
+            line: self.line,
+            column: self.column,
+            end_line: self.line,
+            end_column: self.column,
+        }
+    }
+    
+    /// Следующий токен. При ошибке лексер уже стоит за плохим фрагментом,
+    /// так что разбор можно продолжать повторным вызовом.
+    pub fn next_token(&mut self) -> Result<SpannedToken, LexError> {
         loop {
             self.skip_whitespace();
             
             // Пропустить комментарии
             if self.current_char == Some('/') && 
                (self.peek(1) == Some('/') || self.peek(1) == Some('*')) {
-                self.skip_comment();
+                self.skip_comment()?;
                 continue;
             }
             
             break;
         }
         
+        let start = self.mark();
+        let token = self.scan_token(start)?;
+        Ok(SpannedToken { token, span: start.to(self.mark()) })
+    }
+    
+    fn scan_token(&mut self, start: Span) -> Result<Token, LexError> {
         match self.current_char {
-            None => Token::КонецФайла,
+            None => Ok(Token::КонецФайла),
             Some(ch) => {
                 if ch.is_numeric() {
-                    return Token::Число(self.read_number());
+                    return Ok(Token::Число(self.read_number(start)?));
                 }
                 
                 if ch.is_alphabetic() || is_cyrillic(ch) || ch == '_' {
                     let id = self.read_identifier();
-                    return match id.as_str() {
-                        "пусть" => Token::Пусть,
-                        "функция" | "фн" => Token::Функция,
-                        "если" => Token::Если,
-                        "иначе" => Token::Иначе,
-                        "пока" => Token::Пока,
-                        "вернуть" => Token::Вернуть,
-                        "истина" => Token::Истина,
-                        "ложь" => Token::Ложь,
-                        _ => Token::Идентификатор(id),
-                    };
+                    return Ok(keyword(&id).unwrap_or(Token::Идентификатор(id)));
                 }
                 
                 let token = match ch {
                     '+' => Token::Плюс,
                     '-' => Token::Минус,
-                    '*' => Token::Умножить,
+                    '*' => {
+                        self.advance();
+                        if self.current_char == Some('*') {
+                            self.advance();
+                            return Ok(Token::Степень);
+                        }
+                        return Ok(Token::Умножить);
+                    }
+                    '%' => Token::Остаток,
+                    '^' => Token::Степень,
                     '/' => Token::Разделить,
                     '(' => Token::ЛевСкобка,
                     ')' => Token::ПравСкобка,
                     '{' => Token::ЛевФигСкобка,
                     '}' => Token::ПравФигСкобка,
+                    '[' => Token::ЛевКвСкобка,
+                    ']' => Token::ПравКвСкобка,
                     ',' => Token::Запятая,
                     ';' => Token::ТочкаЗапятая,
+                    ':' => Token::Двоеточие,
+                    '.' => Token::Точка,
                     '"' => {
-                        return Token::Строка(self.read_string());
+                        return self.read_string(start);
                     }
                     '=' => {
                         self.advance();
                         if self.current_char == Some('=') {
                             self.advance();
-                            return Token::Равно;
+                            return Ok(Token::Равно);
                         }
-                        return Token::Присвоить;
+                        return Ok(Token::Присвоить);
                     }
                     '!' => {
                         self.advance();
                         if self.current_char == Some('=') {
                             self.advance();
-                            return Token::НеРавно;
+                            return Ok(Token::НеРавно);
                         }
-                        panic!("Неожиданный символ после !");
+                        return Ok(Token::Не);
+                    }
+                    '&' | '|' => {
+                        self.advance();
+                        if self.current_char == Some(ch) {
+                            self.advance();
+                            return Ok(if ch == '&' { Token::И } else { Token::Или });
+                        }
+                        let word = if ch == '&' { "и" } else { "или" };
+                        return Err(LexError {
+                            code: codes::UNKNOWN_CHARACTER,
+                            message: format!(
+                                "Одиночный '{0}': логическое «{1}» записывается как '{0}{0}' или '{1}'",
+                                ch, word
+                            ),
+                            span: start.to(self.mark()),
+                        });
                     }
                     '>' => {
                         self.advance();
                         if self.current_char == Some('=') {
                             self.advance();
-                            return Token::БольшеРавно;
+                            return Ok(Token::БольшеРавно);
                         }
-                        return Token::Больше;
+                        return Ok(Token::Больше);
                     }
                     '<' => {
                         self.advance();
                         if self.current_char == Some('=') {
                             self.advance();
-                            return Token::МеньшеРавно;
+                            return Ok(Token::МеньшеРавно);
                         }
-                        return Token::Меньше;
+                        return Ok(Token::Меньше);
+                    }
+                    _ => {
+                        self.advance();
+                        return Err(LexError {
+                            code: codes::UNKNOWN_CHARACTER,
+                            message: format!("Неизвестный символ '{}' (U+{:04X})", ch, ch as u32),
+                            span: start.to(self.mark()),
+                        });
                     }
-                    _ => panic!("Неизвестный символ: {}", ch),
                 };
                 
                 self.advance();
-                token
+                Ok(token)
             }
         }
     }
     
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    /// Разбивает весь текст на токены. Каждая ошибка попадает в список,
+    /// а на её месте в потоке остаётся `Token::Ошибка`.
+    pub fn tokenize(&mut self) -> (Vec<SpannedToken>, Vec<LexError>) {
         let mut tokens = Vec::new();
+        let mut errors = Vec::new();
         loop {
             let token = self.next_token();
-            if token == Token::КонецФайла {
-                tokens.push(token);
-                break;
+            errors.append(&mut self.errors);
+            match token {
+                Ok(token) => {
+                    let is_end = token.token == Token::КонецФайла;
+                    tokens.push(token);
+                    if is_end {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    tokens.push(SpannedToken { token: Token::Ошибка, span: err.span });
+                    errors.push(err);
+                }
             }
-            tokens.push(token);
         }
-        tokens
+        (tokens, errors)
     }
 }
 
-fn is_cyrillic(ch: char) -> bool {
+pub fn is_cyrillic(ch: char) -> bool {
     ('\u{0400}'..='\u{04FF}').contains(&ch)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tokenize(code: &str) -> (Vec<Token>, Vec<LexError>) {
+        let (tokens, errors) = Lexer::new(code.to_string()).tokenize();
+        (tokens.into_iter().map(|token| token.token).collect(), errors)
+    }
+    
+    fn messages(errors: &[LexError]) -> Vec<&str> {
+        errors.iter().map(|err| err.message.as_str()).collect()
+    }
+    
+    #[test]
+    fn string_with_substitutions() {
+        let (tokens, errors) = tokenize(r#""x = {x}, {{}}""#);
+        assert!(errors.is_empty());
+        let Token::Шаблон(parts) = &tokens[0] else {
+            panic!("ожидалась строка с подстановками: {:?}", tokens);
+        };
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], StringPart::Текст("x = ".to_string()));
+        let StringPart::Подстановка(substitution) = &parts[1] else {
+            panic!("ожидалась подстановка: {:?}", parts);
+        };
+        let substitution: Vec<_> = substitution.iter().map(|token| token.token.clone()).collect();
+        assert_eq!(substitution, [Token::Идентификатор("x".to_string()), Token::ПравФигСкобка]);
+        assert_eq!(parts[2], StringPart::Текст(", {}".to_string()));
+    }
+    
+    #[test]
+    fn error_in_substitution_keeps_the_string() {
+        let (tokens, errors) = tokenize(r#"печать("a {1 @ 2} b"); печать("ok");"#);
+        assert_eq!(messages(&errors), ["Неизвестный символ '@' (U+0040)"]);
+        assert_eq!(errors[0].span.column, 14);
+        let Token::Шаблон(parts) = &tokens[2] else {
+            panic!("ожидалась строка с подстановками: {:?}", tokens);
+        };
+        assert_eq!(parts[2], StringPart::Текст(" b".to_string()));
+        assert_eq!(tokens[7], Token::Строка("ok".to_string()));
+    }
+    
+    #[test]
+    fn unclosed_substitution_ends_at_the_closing_quote() {
+        let (tokens, errors) = tokenize(r#"печать("a {"); печать("ok");"#);
+        assert_eq!(messages(&errors), ["Незакрытая подстановка в строке: не найдена '}'"]);
+        assert_eq!((errors[0].span.column, errors[0].span.end_column), (11, 12));
+        assert_eq!(tokens[7], Token::Строка("ok".to_string()));
+        
+        let (_, errors) = tokenize("печать(\"a {x\nпечать(\"ok\");");
+        assert_eq!(errors[0].message, "Незакрытая подстановка в строке: не найдена '}'");
+    }
+    
+    #[test]
+    fn tokens_carry_positions() {
+        let (tokens, _) = Lexer::new("пусть x =\n  \"ё\";".to_string()).tokenize();
+        let string = &tokens[3];
+        assert_eq!(string.token, Token::Строка("ё".to_string()));
+        assert_eq!((string.span.line, string.span.column), (2, 3));
+        assert_eq!((string.span.end_line, string.span.end_column), (2, 6));
+        // Смещения в байтах: русские буквы занимают по два
+        assert_eq!((string.span.start, string.span.end), (17, 21));
+    }
+    
+    #[test]
+    fn every_bad_character_is_reported() {
+        let (tokens, errors) = tokenize("печать(1 # 2 $ 3);");
+        assert_eq!(
+            messages(&errors),
+            ["Неизвестный символ '#' (U+0023)", "Неизвестный символ '$' (U+0024)"]
+        );
+        assert!(errors.iter().all(|err| err.code == codes::UNKNOWN_CHARACTER));
+        assert_eq!(tokens.iter().filter(|token| **token == Token::Ошибка).count(), 2);
+        assert_eq!(tokens.last(), Some(&Token::КонецФайла));
+    }
+}
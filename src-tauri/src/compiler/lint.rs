@@ -0,0 +1,643 @@
+//! Предупреждения линтера: неиспользуемые имена, затенение, бесконечные
+//! циклы, постоянные условия и лишние случаи в выборе. Предупреждения не мешают запуску программы.
+
+use crate::compiler::ast::*;
+use crate::compiler::diagnostics::{codes, Diagnostic, Label, Stage};
+use crate::compiler::lexer::{Span, MAIN_FILE};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Имя файла настроек линтера в папке проекта.
+pub const CONFIG_FILE: &str = "rus-lint.json";
+
+/// Правила линтера: имя для файла настроек и код диагностики.
+pub const RULES: &[(&str, &str)] = &[
+    ("unused-variable", codes::UNUSED_VARIABLE),
+    ("unused-function", codes::UNUSED_FUNCTION),
+    ("shadowed-global", codes::SHADOWED_GLOBAL),
+    ("infinite-loop", codes::INFINITE_LOOP),
+    ("constant-condition", codes::CONSTANT_CONDITION),
+    ("unreachable-case", codes::UNREACHABLE_CASE),
+    ("overlapping-case", codes::OVERLAPPING_CASE),
+];
+
+/// Настройки линтера из `rus-lint.json`, например:
+/// `{ "rules": { "unused-variable": false } }`.
+/// Правило можно назвать по имени или по коду; не упомянутые правила включены.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LintConfig {
+    #[serde(default)]
+    rules: HashMap<String, bool>,
+}
+
+impl LintConfig {
+    /// Читает настройки из папки проекта. Если файла нет, все правила включены.
+    pub fn load(project_dir: &Path) -> Result<Self, String> {
+        let path = project_dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Не удалось прочитать {}: {}", CONFIG_FILE, e))?;
+        let config: LintConfig = serde_json::from_str(&text)
+            .map_err(|e| format!("Ошибка в {}: {}", CONFIG_FILE, e))?;
+
+        for key in config.rules.keys() {
+            if !RULES.iter().any(|(name, code)| key == name || key == code) {
+                return Err(format!("Неизвестное правило '{}' в {}", key, CONFIG_FILE));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn is_enabled(&self, code: &str) -> bool {
+        let Some((name, _)) = RULES.iter().find(|(_, c)| *c == code) else {
+            return true;
+        };
+        self.rules
+            .get(*name)
+            .or_else(|| self.rules.get(code))
+            .copied()
+            .unwrap_or(true)
+    }
+}
+
+// Значение выражения, известное без запуска программы
+#[derive(Debug, Clone, PartialEq)]
+enum Const {
+    Число(f64),
+    Строка(String),
+    Булево(bool),
+}
+
+fn const_eval(expr: &Expr) -> Option<Const> {
+    match &expr.kind {
+        ExprKind::Число(n) => Some(Const::Число(*n)),
+        ExprKind::Строка(s) => Some(Const::Строка(s.clone())),
+        ExprKind::Булево(b) => Some(Const::Булево(*b)),
+        ExprKind::БинарнаяОперация { левый, оператор, правый } => {
+            let left = const_eval(левый)?;
+            let right = const_eval(правый)?;
+            match (left, оператор, right) {
+                (Const::Число(l), BinOp::Плюс, Const::Число(r)) => Some(Const::Число(l + r)),
+                (Const::Число(l), BinOp::Минус, Const::Число(r)) => Some(Const::Число(l - r)),
+                (Const::Число(l), BinOp::Умножить, Const::Число(r)) => Some(Const::Число(l * r)),
+                (Const::Число(l), BinOp::Разделить, Const::Число(r)) if r != 0.0 => {
+                    Some(Const::Число(l / r))
+                }
+                (Const::Число(l), BinOp::Остаток, Const::Число(r)) if r != 0.0 => {
+                    Some(Const::Число(l % r))
+                }
+                (Const::Число(l), BinOp::ЦелоеДеление, Const::Число(r)) if r != 0.0 => {
+                    Some(Const::Число((l / r).trunc()))
+                }
+                (Const::Число(l), BinOp::Степень, Const::Число(r)) => Some(Const::Число(l.powf(r))),
+                (Const::Число(l), BinOp::Равно, Const::Число(r)) => Some(Const::Булево(l == r)),
+                (Const::Число(l), BinOp::НеРавно, Const::Число(r)) => Some(Const::Булево(l != r)),
+                (Const::Число(l), BinOp::Больше, Const::Число(r)) => Some(Const::Булево(l > r)),
+                (Const::Число(l), BinOp::Меньше, Const::Число(r)) => Some(Const::Булево(l < r)),
+                (Const::Число(l), BinOp::БольшеРавно, Const::Число(r)) => Some(Const::Булево(l >= r)),
+                (Const::Число(l), BinOp::МеньшеРавно, Const::Число(r)) => Some(Const::Булево(l <= r)),
+                (Const::Строка(l), BinOp::Плюс, Const::Строка(r)) => Some(Const::Строка(l + &r)),
+                (Const::Строка(l), BinOp::Равно, Const::Строка(r)) => Some(Const::Булево(l == r)),
+                (Const::Строка(l), BinOp::НеРавно, Const::Строка(r)) => Some(Const::Булево(l != r)),
+                (Const::Булево(l), BinOp::Равно, Const::Булево(r)) => Some(Const::Булево(l == r)),
+                (Const::Булево(l), BinOp::НеРавно, Const::Булево(r)) => Some(Const::Булево(l != r)),
+                (Const::Булево(l), BinOp::И, Const::Булево(r)) => Some(Const::Булево(l && r)),
+                (Const::Булево(l), BinOp::Или, Const::Булево(r)) => Some(Const::Булево(l || r)),
+                _ => None,
+            }
+        }
+        ExprKind::Отрицание(операнд) => match const_eval(операнд)? {
+            Const::Булево(b) => Some(Const::Булево(!b)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Есть ли в теле цикла выход из него (вложенные функции не считаются).
+// `прервать` из вложенного цикла выходит только из вложенного.
+fn has_exit(stmts: &[Stmt], own_loop: bool) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Вернуть(_) | StmtKind::Бросить(_) => true,
+        StmtKind::Прервать => own_loop,
+        StmtKind::Если { тогда, иначе, .. } => {
+            has_exit(тогда, own_loop) || иначе.as_deref().is_some_and(|b| has_exit(b, own_loop))
+        }
+        StmtKind::Выбор { случаи, иначе, .. } => {
+            случаи.iter().any(|case| has_exit(&case.body, own_loop))
+                || иначе.as_deref().is_some_and(|b| has_exit(b, own_loop))
+        }
+        StmtKind::Попытка { тело, перехват, наконец } => {
+            has_exit(тело, own_loop)
+                || перехват.as_ref().is_some_and(|catch| has_exit(&catch.body, own_loop))
+                || наконец.as_deref().is_some_and(|b| has_exit(b, own_loop))
+        }
+        StmtKind::Пока { тело, .. }
+        | StmtKind::Для { тело, .. }
+        | StmtKind::ДляКаждого { тело, .. } => has_exit(тело, false),
+        _ => false,
+    })
+}
+
+// Всякое ли значение, подходящее под `later`, подходит и под `earlier`.
+// Наследование классов не учитывается, поэтому ответ «нет» бывает и зря.
+fn covers(earlier: &Pattern, later: &Pattern) -> bool {
+    match (&earlier.kind, &later.kind) {
+        (PatternKind::Переменная(_), _) => true,
+        (PatternKind::Число(a), PatternKind::Число(b)) => a == b,
+        (PatternKind::Число(a), PatternKind::Диапазон(from, to)) => a == from && a == to,
+        (PatternKind::Диапазон(from, to), PatternKind::Число(b)) => from <= b && b <= to,
+        (PatternKind::Диапазон(from, to), PatternKind::Диапазон(b_from, b_to)) => {
+            from <= b_from && b_to <= to
+        }
+        (PatternKind::Строка(a), PatternKind::Строка(b)) => a == b,
+        (PatternKind::Булево(a), PatternKind::Булево(b)) => a == b,
+        (PatternKind::Список(a), PatternKind::Список(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| covers(a, b))
+        }
+        (
+            PatternKind::Структура { имя: a_name, поля: a_fields },
+            PatternKind::Структура { имя: b_name, поля: b_fields },
+        ) => {
+            a_name == b_name
+                && a_fields.iter().all(|a| {
+                    a.pattern.is_catch_all()
+                        || b_fields.iter().any(|b| b.name == a.name && covers(&a.pattern, &b.pattern))
+                })
+        }
+        _ => false,
+    }
+}
+
+// Переменные одной области видимости: всей программы или тела функции
+#[derive(Default)]
+struct Frame {
+    declared: Vec<(String, Span)>,
+    read: HashSet<String>,
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    globals: HashSet<String>,
+    functions: Vec<(String, Span)>,
+    called: HashSet<String>,
+    current_function: Option<String>,
+    frame: Frame,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Проверяет программу и возвращает предупреждения включённых правил.
+pub fn lint(program: &Program, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        config,
+        globals: HashSet::new(),
+        functions: Vec::new(),
+        called: HashSet::new(),
+        current_function: None,
+        frame: Frame::default(),
+        diagnostics: Vec::new(),
+    };
+    linter.collect_globals(&program.statements);
+    linter.walk_block(&program.statements);
+    linter.finish_frame();
+
+    for (name, span) in std::mem::take(&mut linter.functions) {
+        if !linter.called.contains(&name) && span.file == MAIN_FILE {
+            linter.warn(
+                codes::UNUSED_FUNCTION,
+                format!("Функция '{}' объявлена, но нигде не вызывается", name),
+                span,
+            );
+        }
+    }
+
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| d.span.map(|s| (s.file, s.start)));
+    diagnostics
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, code: &str, message: String, span: Span) {
+        if !self.config.is_enabled(code) {
+            return;
+        }
+        let rule = RULES.iter().find(|(_, c)| *c == code).map(|(name, _)| *name).unwrap_or(code);
+        self.diagnostics.push(
+            Diagnostic::warning(Stage::Semantic, code, message)
+                .with_span(span)
+                .with_note(format!(
+                    "правило можно отключить в {}: \"{}\": false",
+                    CONFIG_FILE, rule
+                )),
+        );
+    }
+
+    // Имена, объявленные на верхнем уровне программы; у блоков свои области
+    fn collect_globals(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            if let StmtKind::ОбъявлениеПеременной { имя, .. } = &stmt.kind {
+                self.globals.insert(имя.clone());
+            }
+        }
+    }
+
+    // Проверяет область, которая закончилась, и возвращает прочитанные в ней
+    // имена из внешних областей. Подключённый файл объявляет имена для других
+    // файлов, поэтому неиспользованные имена ищем только в главном.
+    fn finish_frame(&mut self) -> HashSet<String> {
+        let mut frame = std::mem::take(&mut self.frame);
+        for (name, span) in frame.declared {
+            if !frame.read.remove(&name) && !name.starts_with('_') && span.file == MAIN_FILE {
+                self.warn(
+                    codes::UNUSED_VARIABLE,
+                    format!("Переменная '{}' объявлена, но её значение нигде не используется", name),
+                    span,
+                );
+            }
+        }
+        frame.read
+    }
+
+    // Тело функции в своей области; чтение внешних переменных засчитывается внешней.
+    // `owner` — чьи это параметры, для сообщения: «функции 'f'», «метода 'м'»
+    fn walk_function_body(&mut self, owner: &str, params: &[Param], body: &[Stmt]) {
+        for param in params {
+            if self.globals.contains(&param.name) {
+                self.warn(
+                    codes::SHADOWED_GLOBAL,
+                    format!(
+                        "Параметр '{}' {} совпадает с именем глобальной переменной",
+                        param.name, owner
+                    ),
+                    param.span,
+                );
+            }
+        }
+
+        let outer_frame = std::mem::take(&mut self.frame);
+        self.walk_block(body);
+        let mut read = self.finish_frame();
+        for param in params {
+            read.remove(&param.name);
+        }
+        self.frame = outer_frame;
+        self.frame.read.extend(read);
+    }
+
+    fn walk_block(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.walk_statement(stmt);
+        }
+    }
+
+    fn walk_statement(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::ОбъявлениеПеременной { имя, значение, .. } => {
+                self.walk_expression(значение);
+                if !self.frame.declared.iter().any(|(name, _)| name == имя) {
+                    self.frame.declared.push((имя.clone(), stmt.span));
+                }
+            }
+            StmtKind::Если { условие, тогда, иначе } => {
+                self.walk_expression(условие);
+                if let Some(Const::Булево(value)) = const_eval(условие) {
+                    let message = if !value {
+                        "Условие всегда ложно, эта ветка никогда не выполнится"
+                    } else if иначе.is_some() {
+                        "Условие всегда истинно, ветка 'иначе' никогда не выполнится"
+                    } else {
+                        "Условие всегда истинно, проверку можно убрать"
+                    };
+                    self.warn(codes::CONSTANT_CONDITION, message.to_string(), условие.span);
+                }
+                self.walk_block(тогда);
+                if let Some(иначе) = иначе {
+                    self.walk_block(иначе);
+                }
+            }
+            StmtKind::Выбор { значение, случаи, иначе } => {
+                self.walk_expression(значение);
+                self.check_cases(случаи, иначе.is_some());
+                for case in случаи {
+                    for pattern in &case.patterns {
+                        for (name, span) in pattern.bindings() {
+                            if !self.frame.declared.iter().any(|(declared, _)| declared == name) {
+                                self.frame.declared.push((name.to_string(), span));
+                            }
+                        }
+                    }
+                    self.walk_block(&case.body);
+                }
+                if let Some(иначе) = иначе {
+                    self.walk_block(иначе);
+                }
+            }
+            StmtKind::Попытка { тело, перехват, наконец } => {
+                self.walk_block(тело);
+                if let Some(catch) = перехват {
+                    if !self.frame.declared.iter().any(|(name, _)| *name == catch.name) {
+                        self.frame.declared.push((catch.name.clone(), catch.span));
+                    }
+                    self.walk_block(&catch.body);
+                }
+                if let Some(наконец) = наконец {
+                    self.walk_block(наконец);
+                }
+            }
+            StmtKind::Пока { условие, тело } => {
+                self.walk_expression(условие);
+                match const_eval(условие) {
+                    Some(Const::Булево(true)) if !has_exit(тело, true) => {
+                        self.warn(
+                            codes::INFINITE_LOOP,
+                            "Бесконечный цикл: условие всегда истинно, а выхода из цикла нет"
+                                .to_string(),
+                            stmt.span,
+                        );
+                    }
+                    Some(Const::Булево(false)) => {
+                        self.warn(
+                            codes::CONSTANT_CONDITION,
+                            "Условие всегда ложно, тело цикла ни разу не выполнится".to_string(),
+                            условие.span,
+                        );
+                    }
+                    _ => {}
+                }
+                self.walk_block(тело);
+            }
+            // Переменная цикла не считается неиспользуемой: она нужна для счёта повторов
+            StmtKind::Для { начало, конец, шаг, тело, .. } => {
+                self.walk_expression(начало);
+                self.walk_expression(конец);
+                if let Some(шаг) = шаг {
+                    self.walk_expression(шаг);
+                }
+                self.walk_block(тело);
+            }
+            StmtKind::ДляКаждого { коллекция, тело, .. } => {
+                self.walk_expression(коллекция);
+                self.walk_block(тело);
+            }
+            StmtKind::Вернуть(expr) | StmtKind::Бросить(expr) | StmtKind::Выражение(expr) => self.walk_expression(expr),
+            StmtKind::Прервать | StmtKind::Продолжить | StmtKind::ОбъявлениеСтруктуры { .. } => {}
+            StmtKind::ОбъявлениеФункции { имя, параметры, тело, .. } => {
+                self.functions.push((имя.clone(), stmt.span));
+                let outer_function = self.current_function.replace(имя.clone());
+                self.walk_function_body(&format!("функции '{}'", имя), параметры, тело);
+                self.current_function = outer_function;
+            }
+            // Методы вызываются через объект, поэтому неиспользуемыми не считаются
+            StmtKind::ОбъявлениеКласса { поля, методы, .. } => {
+                for value in поля.iter().filter_map(|field| field.value.as_ref()) {
+                    self.walk_expression(value);
+                }
+                for method in методы {
+                    let outer_function = self.current_function.take();
+                    let owner = format!("метода '{}'", method.name);
+                    self.walk_function_body(&owner, &method.function.params, &method.function.body);
+                    self.current_function = outer_function;
+                }
+            }
+        }
+    }
+
+    // Случаи, которые уже разобраны выше, и пересекающиеся диапазоны
+    fn check_cases(&mut self, cases: &[Case], has_else: bool) {
+        let mut earlier: Vec<(&Pattern, Span)> = Vec::new();
+        for case in cases {
+            let covered: Vec<Option<Span>> = case
+                .patterns
+                .iter()
+                .map(|pattern| {
+                    earlier.iter().find(|(prev, _)| covers(prev, pattern)).map(|(_, span)| *span)
+                })
+                .collect();
+
+            if let Some(Some(first)) = covered.iter().find(|span| span.is_some()) {
+                if covered.iter().all(Option::is_some) {
+                    self.warn_with_label(
+                        codes::UNREACHABLE_CASE,
+                        "Случай никогда не выполнится: все его значения разобраны выше".to_string(),
+                        case.span,
+                        *first,
+                    );
+                } else {
+                    for (pattern, span) in case.patterns.iter().zip(&covered) {
+                        if let Some(span) = span {
+                            self.warn_with_label(
+                                codes::UNREACHABLE_CASE,
+                                "Образец уже разобран выше и здесь никогда не подойдёт".to_string(),
+                                pattern.span,
+                                *span,
+                            );
+                        }
+                    }
+                }
+            }
+
+            for (pattern, span) in case.patterns.iter().zip(&covered) {
+                let PatternKind::Диапазон(from, to) = pattern.kind else {
+                    continue;
+                };
+                if span.is_some() {
+                    continue;
+                }
+                let overlap = earlier.iter().find_map(|(prev, prev_span)| match prev.kind {
+                    PatternKind::Диапазон(a, b) if a <= to && from <= b => {
+                        Some((a.max(from), b.min(to), *prev_span))
+                    }
+                    _ => None,
+                });
+                if let Some((start, end, prev_span)) = overlap {
+                    let values = if start == end {
+                        format!("значение {} попадёт", start)
+                    } else {
+                        format!("значения от {} до {} попадут", start, end)
+                    };
+                    self.warn_with_label(
+                        codes::OVERLAPPING_CASE,
+                        format!("Диапазон пересекается со случаем выше: {} туда, а не сюда", values),
+                        pattern.span,
+                        prev_span,
+                    );
+                }
+            }
+
+            earlier.extend(case.patterns.iter().map(|pattern| (pattern, case.span)));
+        }
+
+        if has_else {
+            let catch_all = cases.iter().flat_map(|case| &case.patterns).find(|p| p.is_catch_all());
+            if let Some(pattern) = catch_all {
+                self.warn(
+                    codes::UNREACHABLE_CASE,
+                    "Образец подходит под любое значение, поэтому ветка 'иначе' никогда не выполнится"
+                        .to_string(),
+                    pattern.span,
+                );
+            }
+        }
+    }
+
+    fn warn_with_label(&mut self, code: &str, message: String, span: Span, earlier: Span) {
+        let reported = self.diagnostics.len();
+        self.warn(code, message, span);
+        if let Some(diagnostic) = self.diagnostics.get_mut(reported) {
+            diagnostic.labels.push(Label { span: earlier, message: "уже разобрано здесь".to_string() });
+        }
+    }
+
+    fn walk_expression(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Число(_)
+            | ExprKind::Строка(_)
+            | ExprKind::Булево(_)
+            | ExprKind::Этот
+            | ExprKind::Ошибка => {}
+            ExprKind::Шаблон(parts) => {
+                for part in parts {
+                    if let TemplatePart::Выражение(expr) = part {
+                        self.walk_expression(expr);
+                    }
+                }
+            }
+            // Имя функции без вызова — функция-значение, это тоже её использование
+            ExprKind::Идентификатор(name) => {
+                self.frame.read.insert(name.clone());
+                if self.current_function.as_ref() != Some(name) {
+                    self.called.insert(name.clone());
+                }
+            }
+            ExprKind::БинарнаяОперация { левый, правый, .. } => {
+                self.walk_expression(левый);
+                self.walk_expression(правый);
+            }
+            ExprKind::ВызовФункции { имя, аргументы } => {
+                // Рекурсивный вызов изнутри самой функции использованием не считается
+                if self.current_function.as_ref() != Some(имя) {
+                    self.called.insert(имя.clone());
+                }
+                // Вызов функции из переменной — чтение этой переменной
+                self.frame.read.insert(имя.clone());
+                for arg in аргументы {
+                    self.walk_expression(arg);
+                }
+            }
+            ExprKind::Вызов { функция, аргументы } => {
+                self.walk_expression(функция);
+                for arg in аргументы {
+                    self.walk_expression(arg);
+                }
+            }
+            ExprKind::Лямбда(lambda) => {
+                self.walk_function_body("анонимной функции", &lambda.params, &lambda.body)
+            }
+            ExprKind::Отрицание(операнд) => self.walk_expression(операнд),
+            ExprKind::Список(элементы) => {
+                for item in элементы {
+                    self.walk_expression(item);
+                }
+            }
+            ExprKind::Словарь(пары) => {
+                for (key, value) in пары {
+                    self.walk_expression(key);
+                    self.walk_expression(value);
+                }
+            }
+            ExprKind::Индекс { объект, индекс } => {
+                self.walk_expression(объект);
+                self.walk_expression(индекс);
+            }
+            ExprKind::ПрисваиваниеПоИндексу { объект, индекс, значение } => {
+                self.walk_expression(объект);
+                self.walk_expression(индекс);
+                self.walk_expression(значение);
+            }
+            ExprKind::Поле { объект, .. } => self.walk_expression(объект),
+            ExprKind::ВызовМетода { объект, аргументы, .. } => {
+                self.walk_expression(объект);
+                for arg in аргументы {
+                    self.walk_expression(arg);
+                }
+            }
+            ExprKind::ПрисваиваниеПолю { объект, значение, .. } => {
+                self.walk_expression(объект);
+                self.walk_expression(значение);
+            }
+            ExprKind::СозданиеСтруктуры { поля, .. } => {
+                for field in поля {
+                    self.walk_expression(&field.value);
+                }
+            }
+            ExprKind::Присваивание { значение, .. } => self.walk_expression(значение),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+    use crate::compiler::parser::Parser;
+    
+    // Предупреждения линтера со всеми правилами: код и сообщение
+    fn warnings(code: &str) -> Vec<(String, String)> {
+        let (tokens, _) = Lexer::new(code.to_string()).tokenize();
+        let (program, errors) = Parser::new(tokens).parse();
+        assert!(errors.is_empty(), "синтаксические ошибки: {:?}", errors);
+        lint(&program, &LintConfig::default())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.message))
+            .collect()
+    }
+    
+    #[test]
+    fn constant_condition_mentions_иначе_only_when_it_exists() {
+        let found = warnings("если (истина) { печать(1); }");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, codes::CONSTANT_CONDITION);
+        assert_eq!(found[0].1, "Условие всегда истинно, проверку можно убрать");
+        
+        let found = warnings("если (1 < 2) { печать(1); } иначе { печать(2); }");
+        assert_eq!(found[0].1, "Условие всегда истинно, ветка 'иначе' никогда не выполнится");
+    }
+    
+    #[test]
+    fn rules_can_be_disabled() {
+        let code = "функция f() { пусть x = 1; } f();";
+        assert_eq!(warnings(code)[0].0, codes::UNUSED_VARIABLE);
+        let config: LintConfig = serde_json::from_str(r#"{ "rules": { "unused-variable": false } }"#).unwrap();
+        let (tokens, _) = Lexer::new(code.to_string()).tokenize();
+        let (program, _) = Parser::new(tokens).parse();
+        assert!(lint(&program, &config).is_empty());
+    }
+    
+    #[test]
+    fn parameters_of_lambdas_and_methods_shadow_globals_too() {
+        let code = "пусть x = 1;
+                    пусть f = фн(x) { вернуть x; };
+                    класс К { функция м(x) { вернуть x; } }
+                    функция g(x) { вернуть x; }
+                    печать(x, f(1), К().м(1), g(1));";
+        let found: Vec<String> = warnings(code).into_iter().map(|(_, message)| message).collect();
+        assert_eq!(
+            found,
+            [
+                "Параметр 'x' анонимной функции совпадает с именем глобальной переменной",
+                "Параметр 'x' метода 'м' совпадает с именем глобальной переменной",
+                "Параметр 'x' функции 'g' совпадает с именем глобальной переменной",
+            ]
+        );
+    }
+    
+    #[test]
+    fn unused_names_of_imported_files_are_not_reported() {
+        let (tokens, _) = Lexer::with_file("функция f() { пусть x = 1; }".to_string(), 1).tokenize();
+        let (program, _) = Parser::new(tokens).parse();
+        assert!(lint(&program, &LintConfig::default()).is_empty());
+    }
+}
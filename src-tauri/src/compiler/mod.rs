@@ -2,16 +2,29 @@ pub mod lexer;
 pub mod ast;
 pub mod parser;
 pub mod interpreter;
+pub mod diagnostics;
+pub mod suggest;
+pub mod layout;
+pub mod homoglyphs;
+pub mod semantic;
+pub mod lint;
+pub mod types;
+pub mod modules;
+
+use diagnostics::{codes, Diagnostic, Stage};
 
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompilationResult {
     pub success: bool,
     pub output: String,
-    pub errors: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
     pub plots: Vec<PlotData>,
+    // Файлы программы по номерам из `Span::file`; главный — первый
+    pub files: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,93 +35,114 @@ pub struct PlotData {
     pub timestamp: i64, // ДОБАВИЛИ
 }
 
-pub fn compile_and_run(code: String) -> CompilationResult {
+const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Компилирует и выполняет программу. `path` — файл, в котором лежит код
+/// (его может ещё не быть): пути в `подключить` считаются от его папки.
+/// `project_dir` — папка проекта, из которой читаются настройки линтера;
+/// без неё они ищутся рядом с файлом.
+pub fn compile_and_run(code: String, path: Option<&Path>, project_dir: Option<&Path>) -> CompilationResult {
+    let loaded = modules::load(code, path);
+    run(loaded, project_dir.or(path.and_then(Path::parent)))
+}
+
+/// Компилирует и выполняет программу из файла `path` вместе с подключёнными
+/// к ней файлами. Без папки проекта настройки линтера ищутся рядом с файлом.
+pub fn compile_and_run_file(path: &Path, project_dir: Option<&Path>) -> CompilationResult {
+    let loaded = modules::load_file(path);
+    run(loaded, project_dir.or(path.parent()))
+}
+
+fn run(loaded: modules::LoadedProgram, project_dir: Option<&Path>) -> CompilationResult {
     let output = Arc::new(Mutex::new(String::new()));
     let output_clone = output.clone();
     
     let plots = Arc::new(Mutex::new(Vec::new()));
     let plots_clone = plots.clone();
     
-    let mut errors = Vec::new();
-    
-    // Лексический анализ
-    let mut lexer = lexer::Lexer::new(code);
-    let tokens = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        lexer.tokenize()
-    })) {
-        Ok(tokens) => tokens,
-        Err(_) => {
-            errors.push("Ошибка лексического анализа".to_string());
-            return CompilationResult {
-                success: false,
-                output: String::new(),
-                errors,
-                plots: Vec::new(),
-            };
-        }
-    };
+    // Лексический анализ и парсинг всех файлов (парсинг продолжается и после
+    // лексических ошибок, чтобы найти все синтаксические)
+    let modules::LoadedProgram { modules, files, mut diagnostics } = loaded;
     
-    // Парсинг
-    let mut parser = parser::Parser::new(tokens);
-    let program = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        parser.parse()
-    })) {
-        Ok(program) => program,
-        Err(e) => {
-            let error_msg = if let Some(s) = e.downcast_ref::<String>() {
-                s.clone()
-            } else if let Some(s) = e.downcast_ref::<&str>() {
-                s.to_string()
-            } else {
-                "Ошибка синтаксического анализа".to_string()
-            };
-            errors.push(error_msg);
-            return CompilationResult {
-                success: false,
-                output: String::new(),
-                errors,
-                plots: Vec::new(),
-            };
+    // Статическая проверка имеет смысл только для полностью разобранной программы
+    if !diagnostics.iter().any(Diagnostic::is_error) {
+        diagnostics.extend(semantic::check_modules(&modules));
+        diagnostics.extend(types::check_modules(&modules));
+        
+        let lint_config = match project_dir.map(lint::LintConfig::load) {
+            Some(Ok(config)) => config,
+            Some(Err(message)) => {
+                diagnostics.push(Diagnostic::warning(Stage::Semantic, codes::LINT_CONFIG, message));
+                lint::LintConfig::default()
+            }
+            None => lint::LintConfig::default(),
+        };
+        for module in &modules {
+            diagnostics.extend(lint::lint(&module.program, &lint_config));
         }
-    };
-    
-    // Выполнение
-    let mut interpreter = interpreter::Interpreter::new();
+    }
     
-    // Перехватываем вывод
-    interpreter.set_output_handler(Box::new(move |text| {
-        let mut out = output_clone.lock().unwrap();
-        out.push_str(&text);
-        out.push('\n');
-    }));
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return CompilationResult {
+            success: false,
+            output: String::new(),
+            diagnostics,
+            plots: Vec::new(),
+            files,
+        };
+    }
     
-    // Перехватываем графики
-    interpreter.set_plot_handler(Box::new(move |plot| {
-        let mut p = plots_clone.lock().unwrap();
-        p.push(plot);
-    }));
+    // Глубокая рекурсия в программе — это глубокая рекурсия интерпретатора,
+    // поэтому выполняем его в отдельном потоке с большим стеком. Значения
+    // программы не передаются между потоками, так что и интерпретатор
+    // создаётся прямо в нём.
+    let execution = std::thread::Builder::new()
+        .stack_size(INTERPRETER_STACK_SIZE)
+        .spawn(move || {
+            let mut interpreter = interpreter::Interpreter::new();
+            
+            // Перехватываем вывод
+            interpreter.set_output_handler(Box::new(move |text| {
+                let mut out = output_clone.lock().unwrap();
+                out.push_str(&text);
+                out.push('\n');
+            }));
+            
+            // Перехватываем графики
+            interpreter.set_plot_handler(Box::new(move |plot| {
+                let mut p = plots_clone.lock().unwrap();
+                p.push(plot);
+            }));
+            
+            interpreter.execute_modules(&modules)
+        })
+        .expect("Не удалось запустить поток интерпретатора")
+        .join()
+        .expect("Поток интерпретатора аварийно завершился");
     
-    match interpreter.execute(program) {
+    match execution {
         Ok(_) => {
             let final_output = output.lock().unwrap().clone();
             let final_plots = plots.lock().unwrap().clone();
             CompilationResult {
                 success: true,
                 output: final_output,
-                errors: Vec::new(),
+                diagnostics,
                 plots: final_plots,
+                files,
             }
         }
         Err(e) => {
-            errors.push(e);
+            diagnostics.push(e.into());
             let final_output = output.lock().unwrap().clone();
             let final_plots = plots.lock().unwrap().clone();
             CompilationResult {
                 success: false,
                 output: final_output,
-                errors,
+                diagnostics,
                 plots: final_plots,
+                files,
             }
         }
     }
-}
\ No newline at end of file
+}
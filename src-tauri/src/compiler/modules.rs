@@ -0,0 +1,428 @@
+//! Программа из нескольких файлов: `подключить "геометрия.рус";` и
+//! `из "геометрия.рус" подключить площадь, Точка;`. Каждый файл разбирается
+//! отдельно и становится модулем программы: подключённый файл идёт раньше
+//! того, кто его подключил, и попадает в неё один раз, сколько бы файлов его
+//! ни подключали. У каждого модуля своя область видимости: в ней его
+//! объявления и подключённые имена — все объявления верхнего уровня файла
+//! для `подключить` и только перечисленные для `из … подключить`.
+
+use crate::compiler::ast::*;
+use crate::compiler::diagnostics::{codes, Diagnostic, Stage};
+use crate::compiler::lexer::{Lexer, Span};
+use crate::compiler::parser::Parser;
+use crate::compiler::{homoglyphs, layout, suggest};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Файл программы со своей областью видимости.
+pub struct Module {
+    // Номер файла из `Span::file`
+    pub file: u32,
+    pub program: Program,
+    // Подключённые имена: номер модуля, который их объявил, и имя
+    pub imports: Vec<(usize, String)>,
+}
+
+/// Программа, собранная из всех файлов, и ошибки, найденные при разборе.
+pub struct LoadedProgram {
+    // Модули в порядке выполнения: подключённые раньше тех, кто их
+    // подключил, главный файл — последним
+    pub modules: Vec<Module>,
+    // Имена файлов по номерам из `Span::file`
+    pub files: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+struct Loader {
+    // Папка главного файла: имена остальных в сообщениях считаются от неё
+    root: PathBuf,
+    files: Vec<String>,
+    // Подключённые файлы и номера их модулей
+    loaded: HashMap<PathBuf, usize>,
+    // Файлы, которые подключаются прямо сейчас, от главного к самому вложенному
+    chain: Vec<PathBuf>,
+    modules: Vec<Module>,
+    // Имена, объявленные на верхнем уровне каждого модуля
+    declared: Vec<Vec<String>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Программа из строки, например из вкладки редактора с несохранёнными
+/// изменениями. `path` — файл, в котором этот код будет сохранён: пути в
+/// `подключить` считаются от его папки. У новой вкладки файла ещё нет, и
+/// подключать из неё нечего.
+pub fn load(code: String, path: Option<&Path>) -> LoadedProgram {
+    let Some(path) = path else {
+        let mut loader = Loader::new(PathBuf::new());
+        loader.add_file(code, "программа".to_string(), None);
+        return loader.finish();
+    };
+    let name = file_name(path);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut loader = Loader::new(dir.clone());
+    loader.chain.push(path);
+    loader.add_file(code, name, Some(&dir));
+    loader.finish()
+}
+
+/// Программа из файла `path` вместе со всем, что он подключает.
+pub fn load_file(path: &Path) -> LoadedProgram {
+    match std::fs::read_to_string(path) {
+        Ok(code) => load(code, Some(path)),
+        Err(err) => {
+            let name = file_name(path);
+            let mut loader = Loader::new(PathBuf::new());
+            loader.files.push(name.clone());
+            loader.diagnostics.push(Diagnostic::error(
+                Stage::Loading,
+                codes::FILE_NOT_FOUND,
+                format!("Не удалось прочитать файл '{}': {}", name, err),
+            ));
+            loader.finish()
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
+
+impl Loader {
+    fn new(root: PathBuf) -> Self {
+        Loader {
+            root,
+            files: Vec::new(),
+            loaded: HashMap::new(),
+            chain: Vec::new(),
+            modules: Vec::new(),
+            declared: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn finish(self) -> LoadedProgram {
+        LoadedProgram {
+            modules: self.modules,
+            files: self.files,
+            diagnostics: self.diagnostics,
+        }
+    }
+
+    // Имя файла для сообщений: путь от папки главного файла
+    fn display_name(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root).unwrap_or(path).display().to_string()
+    }
+
+    fn error(&mut self, code: &str, message: String, span: Span) -> &mut Diagnostic {
+        self.diagnostics
+            .push(Diagnostic::error(Stage::Loading, code, message).with_span(span));
+        self.diagnostics.last_mut().unwrap()
+    }
+
+    // Разбирает файл, подключает всё, что он просит, и добавляет его модуль в
+    // программу после подключённых. Возвращает номер модуля.
+    // `dir` — папка файла, от неё считаются пути в его `подключить`
+    fn add_file(&mut self, code: String, name: String, dir: Option<&Path>) -> usize {
+        let file = self.files.len() as u32;
+        self.files.push(name);
+
+        let (tokens, lex_errors) = Lexer::with_file(code, file).tokenize();
+        self.diagnostics.extend(lex_errors.into_iter().map(Diagnostic::from));
+        self.diagnostics.extend(layout::check_layout(&tokens));
+        self.diagnostics.extend(homoglyphs::check_homoglyphs(&tokens));
+        let (program, parse_errors) = Parser::new(tokens).parse();
+        self.diagnostics.extend(parse_errors.into_iter().map(Diagnostic::from));
+
+        let declared: Vec<String> = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::ОбъявлениеПеременной { имя, .. }
+                | StmtKind::ОбъявлениеФункции { имя, .. }
+                | StmtKind::ОбъявлениеСтруктуры { имя, .. }
+                | StmtKind::ОбъявлениеКласса { имя, .. } => Some(имя.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut imports: Vec<(usize, String)> = Vec::new();
+        for import in &program.imports {
+            let Some(module) = self.import(import, dir) else {
+                continue;
+            };
+            let names: Vec<(String, Span)> = if import.names.is_empty() {
+                self.declared[module].iter().map(|name| (name.clone(), import.span)).collect()
+            } else {
+                // Неизвестные имена уже найдены в `import`
+                import.names.iter().filter(|(name, _)| self.declared[module].contains(name)).cloned().collect()
+            };
+            for (name, span) in names {
+                if declared.contains(&name) {
+                    self.error(
+                        codes::IMPORT_CONFLICT,
+                        format!("Имя '{}' из файла '{}' уже объявлено в этом файле", name, import.path),
+                        span,
+                    )
+                    .notes
+                    .push("переименуйте одно из объявлений или подключите только нужные имена".to_string());
+                    continue;
+                }
+                match imports.iter().find(|(_, imported)| *imported == name) {
+                    Some((other, _)) if *other != module => {
+                        let first = self.files[self.modules[*other].file as usize].clone();
+                        let second = self.files[self.modules[module].file as usize].clone();
+                        self.error(
+                            codes::IMPORT_CONFLICT,
+                            format!("Имя '{}' подключено из двух файлов: '{}' и '{}'", name, first, second),
+                            span,
+                        )
+                        .notes
+                        .push("подключите его только из одного файла".to_string());
+                    }
+                    Some(_) => {}
+                    None => imports.push((module, name)),
+                }
+            }
+        }
+
+        self.modules.push(Module { file, program, imports });
+        self.declared.push(declared);
+        self.modules.len() - 1
+    }
+
+    // Загружает подключённый файл, если он ещё не загружен, и возвращает
+    // номер его модуля; `None` — подключить файл не удалось
+    fn import(&mut self, import: &Import, dir: Option<&Path>) -> Option<usize> {
+        let Some(dir) = dir else {
+            self.error(
+                codes::FILE_NOT_FOUND,
+                format!("Файл '{}' не найден: программа ещё не сохранена", import.path),
+                import.span,
+            )
+            .notes
+            .push("путь считается от папки файла с программой; сохраните её, чтобы подключать файлы".to_string());
+            return None;
+        };
+        let Ok(path) = dir.join(&import.path).canonicalize() else {
+            self.error(
+                codes::FILE_NOT_FOUND,
+                format!("Файл '{}' не найден", import.path),
+                import.span,
+            )
+            .notes
+            .push(format!(
+                "путь считается от папки файла, который подключает: {}",
+                dir.display()
+            ));
+            return None;
+        };
+
+        if let Some(start) = self.chain.iter().position(|loading| *loading == path) {
+            let mut cycle: Vec<String> = self.chain[start..].iter().map(|file| self.display_name(file)).collect();
+            cycle.push(self.display_name(&path));
+            self.error(
+                codes::INCLUDE_CYCLE,
+                format!("Файлы подключают друг друга по кругу: {}", cycle.join(" → ")),
+                import.span,
+            )
+            .notes
+            .push("уберите одно из подключений; общий код можно вынести в отдельный файл".to_string());
+            return None;
+        }
+
+        let module = match self.loaded.get(&path) {
+            Some(module) => *module,
+            None => {
+                let code = match std::fs::read_to_string(&path) {
+                    Ok(code) => code,
+                    Err(err) => {
+                        self.error(
+                            codes::FILE_NOT_FOUND,
+                            format!("Не удалось прочитать файл '{}': {}", import.path, err),
+                            import.span,
+                        );
+                        return None;
+                    }
+                };
+                let name = self.display_name(&path);
+                let file_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+                self.chain.push(path.clone());
+                let module = self.add_file(code, name, Some(&file_dir));
+                self.chain.pop();
+                self.loaded.insert(path, module);
+                module
+            }
+        };
+
+        let declared = &self.declared[module];
+        let mut problems = Vec::new();
+        for (name, span) in &import.names {
+            if declared.contains(name) {
+                continue;
+            }
+            let mut diagnostic = Diagnostic::error(
+                Stage::Loading,
+                codes::UNKNOWN_IMPORT,
+                format!("В файле '{}' нет объявления '{}'", import.path, name),
+            )
+            .with_span(*span);
+            diagnostic.notes.extend(
+                suggest::closest(name, declared.iter().map(String::as_str)).map(suggest::did_you_mean),
+            );
+            problems.push(diagnostic);
+        }
+        self.diagnostics.extend(problems);
+        Some(module)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::semantic;
+    
+    // Временная папка с файлами программы; удаляется после теста
+    struct TempDir(PathBuf);
+    
+    impl TempDir {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let dir = std::env::temp_dir().join(format!("rus-modules-{}-{}", std::process::id(), name));
+            let _ = std::fs::remove_dir_all(&dir);
+            for (file, code) in files {
+                let path = dir.join(file);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, code).unwrap();
+            }
+            TempDir(dir)
+        }
+    }
+    
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+    
+    fn codes(loaded: &LoadedProgram) -> Vec<&str> {
+        loaded.diagnostics.iter().map(|diagnostic| diagnostic.code.as_str()).collect()
+    }
+    
+    // Имена переменных, объявленных на верхнем уровне модулей, по порядку модулей
+    fn declared(loaded: &LoadedProgram) -> Vec<&str> {
+        loaded
+            .modules
+            .iter()
+            .flat_map(|module| &module.program.statements)
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::ОбъявлениеПеременной { имя, .. } => Some(имя.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn imported_file_is_loaded_once_before_its_users() {
+        let dir = TempDir::new(
+            "once",
+            &[
+                ("main.рус", "подключить \"lib/a.рус\";\nподключить \"b.рус\";\nпусть м = 1;"),
+                ("b.рус", "из \"lib/a.рус\" подключить а;\nпусть б = а;"),
+                ("lib/a.рус", "пусть а = 1;"),
+            ],
+        );
+        let loaded = load_file(&dir.0.join("main.рус"));
+        assert!(loaded.diagnostics.is_empty(), "{:?}", codes(&loaded));
+        assert_eq!(loaded.files, ["main.рус", "lib/a.рус", "b.рус"]);
+        assert_eq!(declared(&loaded), ["а", "б", "м"]);
+    }
+    
+    #[test]
+    fn include_cycle_reports_the_chain() {
+        let dir = TempDir::new(
+            "cycle",
+            &[
+                ("main.рус", "подключить \"a.рус\";"),
+                ("a.рус", "подключить \"b.рус\";"),
+                ("b.рус", "подключить \"a.рус\";"),
+            ],
+        );
+        let loaded = load_file(&dir.0.join("main.рус"));
+        assert_eq!(codes(&loaded), [codes::INCLUDE_CYCLE]);
+        let diagnostic = &loaded.diagnostics[0];
+        assert_eq!(diagnostic.message, "Файлы подключают друг друга по кругу: a.рус → b.рус → a.рус");
+        assert_eq!(loaded.files[diagnostic.span.unwrap().file as usize], "b.рус");
+    }
+    
+    #[test]
+    fn unknown_import_suggests_a_declared_name() {
+        let dir = TempDir::new(
+            "names",
+            &[("main.рус", "из \"a.рус\" подключить площядь;"), ("a.рус", "функция площадь() {}")],
+        );
+        let loaded = load_file(&dir.0.join("main.рус"));
+        assert_eq!(codes(&loaded), [codes::UNKNOWN_IMPORT]);
+        assert_eq!(loaded.diagnostics[0].notes, ["возможно, имелось в виду 'площадь'"]);
+    }
+    
+    #[test]
+    fn unsaved_code_imports_relative_to_its_file() {
+        let dir = TempDir::new("unsaved", &[("lib/a.рус", "пусть а = 1;")]);
+        // Файла вкладки на диске ещё нет, но его папка известна
+        let loaded = load("подключить \"lib/a.рус\";".to_string(), Some(&dir.0.join("новый.рус")));
+        assert!(loaded.diagnostics.is_empty(), "{:?}", codes(&loaded));
+        assert_eq!(loaded.files, ["новый.рус", "lib/a.рус"]);
+        
+        let loaded = load("подключить \"lib/a.рус\";".to_string(), None);
+        assert_eq!(codes(&loaded), [codes::FILE_NOT_FOUND]);
+        assert_eq!(loaded.files, ["программа"]);
+    }
+    
+    #[test]
+    fn only_listed_names_are_imported() {
+        let dir = TempDir::new(
+            "listed",
+            &[
+                ("main.рус", "из \"a.рус\" подключить начало;\nпечать(начало().x);\nпечать(сдвиг(1));"),
+                ("a.рус", "структура Точка { x }\nфункция сдвиг(x) { вернуть x + 1; }\nфункция начало() { вернуть Точка(сдвиг(-1)); }"),
+            ],
+        );
+        let loaded = load_file(&dir.0.join("main.рус"));
+        assert!(loaded.diagnostics.is_empty(), "{:?}", codes(&loaded));
+        let found = semantic::check_modules(&loaded.modules);
+        let found: Vec<&str> = found.iter().map(|diagnostic| diagnostic.code.as_str()).collect();
+        assert_eq!(found, [codes::UNDECLARED_FUNCTION]);
+        
+        // Подключённая функция видит объявления своего файла
+        std::fs::write(dir.0.join("main.рус"), "из \"a.рус\" подключить начало;\nпечать(начало().x);").unwrap();
+        let result = crate::compiler::compile_and_run_file(&dir.0.join("main.рус"), None);
+        assert!(result.success, "{:?}", result.diagnostics);
+        assert_eq!(result.output, "0\n");
+    }
+    
+    #[test]
+    fn same_name_from_two_places_is_an_error() {
+        let dir = TempDir::new(
+            "conflict",
+            &[
+                ("main.рус", "подключить \"a.рус\";\nподключить \"b.рус\";\nиз \"a.рус\" подключить y;\nпусть y = 2;"),
+                ("a.рус", "пусть x = 1;\nпусть y = 1;"),
+                ("b.рус", "пусть x = 1;"),
+            ],
+        );
+        let loaded = load_file(&dir.0.join("main.рус"));
+        let messages: Vec<&str> = loaded.diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Имя 'y' из файла 'a.рус' уже объявлено в этом файле",
+                "Имя 'x' подключено из двух файлов: 'a.рус' и 'b.рус'",
+                "Имя 'y' из файла 'a.рус' уже объявлено в этом файле",
+            ]
+        );
+        assert!(codes(&loaded).iter().all(|code| *code == codes::IMPORT_CONFLICT));
+    }
+}
@@ -1,171 +1,1007 @@
-use crate::compiler::lexer::Token;
+use crate::compiler::lexer::{Span, SpannedToken, StringPart, Token};
 use crate::compiler::ast::*;
+use crate::compiler::diagnostics::{codes, Diagnostic, Label, Stage};
+use crate::compiler::suggest;
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    // Вспомогательная пометка, например, где был открыт незакрытый блок
+    pub label: Option<Box<Label>>,
+    pub notes: Vec<String>,
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(err: ParseError) -> Self {
+        let mut diagnostic =
+            Diagnostic::error(Stage::Parsing, err.code, err.message).with_span(err.span);
+        if let Some(label) = err.label {
+            diagnostic.labels.push(*label);
+        }
+        diagnostic.notes = err.notes;
+        diagnostic
+    }
+}
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<SpannedToken>,
     position: usize,
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, position: 0 }
+    pub fn new(tokens: Vec<SpannedToken>) -> Self {
+        Parser { tokens, position: 0, errors: Vec::new() }
     }
-    
+
     fn current_token(&self) -> &Token {
-        self.tokens.get(self.position).unwrap_or(&Token::КонецФайла)
+        self.tokens
+            .get(self.position)
+            .map(|t| &t.token)
+            .unwrap_or(&Token::КонецФайла)
     }
-    
+
+    // Токен через `offset` после текущего
+    fn peek_token(&self, offset: usize) -> &Token {
+        self.tokens
+            .get(self.position + offset)
+            .map(|t| &t.token)
+            .unwrap_or(&Token::КонецФайла)
+    }
+
+    fn current_span(&self) -> Span {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map(|t| t.span)
+            .unwrap_or_default()
+    }
+
+    fn previous_span(&self) -> Option<Span> {
+        self.position
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map(|t| t.span)
+    }
+
+    // Участок от `start` до конца последнего разобранного токена
+    fn span_from(&self, start: Span) -> Span {
+        match self.previous_span() {
+            Some(prev) => start.to(prev),
+            None => start,
+        }
+    }
+
     fn advance(&mut self) {
         self.position += 1;
     }
-    
-    fn expect(&mut self, expected: Token) {
-        if self.current_token() != &expected {
-            panic!("Ожидался {:?}, получен {:?}", expected, self.current_token());
+
+    fn error(&self, code: &'static str, message: String) -> ParseError {
+        ParseError {
+            code,
+            message,
+            span: self.current_span(),
+            label: None,
+            notes: Vec::new(),
         }
-        self.advance();
     }
-    
-    pub fn parse(&mut self) -> Program {
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        if self.current_token() == &expected {
+            self.advance();
+            return Ok(());
+        }
+
+        // Пропущенную ';' показываем сразу за предыдущим токеном, а не на следующей строке
+        if expected == Token::ТочкаЗапятая {
+            if let Some(prev) = self.previous_span() {
+                let end = Span {
+                    start: prev.end,
+                    line: prev.end_line,
+                    column: prev.end_column,
+                    ..prev
+                };
+                return Err(ParseError {
+                    code: codes::MISSING_SEMICOLON,
+                    message: format!("Пропущена точка с запятой ';', дальше идёт: {}", self.current_token()),
+                    span: end,
+                    label: None,
+                    notes: Vec::new(),
+                });
+            }
+        }
+
+        Err(self.error(
+            codes::UNEXPECTED_TOKEN,
+            format!("Ожидается {}, но найдено: {}", expected, self.current_token()),
+        ))
+    }
+
+    fn expect_identifier(&mut self, what: &str) -> Result<String, ParseError> {
+        match self.current_token() {
+            Token::Идентификатор(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            other => Err(self.error(
+                codes::EXPECTED_IDENTIFIER,
+                format!("Ожидается {}, но найдено: {}", what, other),
+            )),
+        }
+    }
+
+    /// Разбирает всю программу. Ошибки не прерывают разбор: парсер
+    /// пропускает испорченную инструкцию и продолжает со следующей,
+    /// так что вместе с ошибками возвращается частично собранная программа.
+    pub fn parse(&mut self) -> (Program, Vec<ParseError>) {
         let mut statements = Vec::new();
-        
+        let mut imports = Vec::new();
+
         while self.current_token() != &Token::КонецФайла {
-            statements.push(self.parse_statement());
+            if self.current_token() == &Token::ПравФигСкобка {
+                let err = self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    "Лишняя закрывающая фигурная скобка '}'".to_string(),
+                );
+                self.errors.push(err);
+                self.advance();
+                continue;
+            }
+            // `из "файл" подключить ...`: «из» — ключевое слово только перед путём
+            let import = self.current_token() == &Token::Подключить
+                || (self.at_word("из") && matches!(self.peek_token(1), Token::Строка(_)));
+            if import {
+                match self.parse_import() {
+                    Ok(import) => imports.push(import),
+                    Err(err) => {
+                        self.errors.push(err);
+                        self.synchronize();
+                    }
+                }
+                continue;
+            }
+            if let Some(stmt) = self.parse_statement_or_recover() {
+                statements.push(stmt);
+            }
         }
-        
-        Program { statements }
+
+        (Program { statements, imports }, std::mem::take(&mut self.errors))
     }
-    
-    fn parse_statement(&mut self) -> Stmt {
+
+    // `подключить "файл";` или `из "файл" подключить имя, имя;`
+    fn parse_import(&mut self) -> Result<Import, ParseError> {
+        let selective = self.at_word("из");
+        if selective {
+            self.advance();
+        } else {
+            self.expect(Token::Подключить)?;
+        }
+
+        let span = self.current_span();
+        let Token::Строка(path) = self.current_token().clone() else {
+            return Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                format!("Ожидается путь к файлу в кавычках, но найдено: {}", self.current_token()),
+            ));
+        };
+        self.advance();
+
+        let mut names = Vec::new();
+        if selective {
+            self.expect(Token::Подключить)?;
+            loop {
+                let name_span = self.current_span();
+                names.push((self.expect_identifier("имя из подключаемого файла")?, name_span));
+                if self.current_token() != &Token::Запятая {
+                    break;
+                }
+                self.advance();
+            }
+        }
+        self.expect(Token::ТочкаЗапятая)?;
+
+        Ok(Import { path, span, names })
+    }
+
+    fn parse_statement_or_recover(&mut self) -> Option<Stmt> {
+        let start = self.position;
+        let first = self.current_token().clone();
+        match self.parse_statement() {
+            Ok(stmt) => Some(stmt),
+            Err(mut err) => {
+                // «пуст x = 1;», «есл (...)»: инструкция начинается с опечатки в ключевом слове
+                if let Token::Идентификатор(name) = &first {
+                    if let Some(word) = suggest::suggest_keyword(name) {
+                        err.notes.push(format!(
+                            "'{}' похоже на ключевое слово '{}' с опечаткой",
+                            name, word
+                        ));
+                    }
+                }
+                self.errors.push(err);
+                self.synchronize();
+                // Гарантируем продвижение, иначе можно застрять на одном токене
+                if self.position == start {
+                    self.advance();
+                }
+                None
+            }
+        }
+    }
+
+    // Пропускает токены до конца испорченной инструкции: за ';', за её
+    // блоком '{...}', перед '}' или перед ключевым словом, с которого
+    // начинается инструкция.
+    fn synchronize(&mut self) {
+        loop {
+            match self.current_token() {
+                // `случай` начинает следующую ветку выбора
+                Token::КонецФайла | Token::ПравФигСкобка | Token::Случай => return,
+                Token::ТочкаЗапятая => {
+                    self.advance();
+                    return;
+                }
+                // Блок инструкции или литерал словаря: за ним инструкция
+                // кончается, если только это не `если` с веткой `иначе`
+                Token::ЛевФигСкобка => {
+                    self.skip_braces();
+                    if !matches!(self.current_token(), Token::Иначе | Token::ТочкаЗапятая) {
+                        return;
+                    }
+                }
+                token if is_statement_start(token) => return,
+                _ => self.advance(),
+            }
+        }
+    }
+
+    // Пропускает '{' вместе со всем до парной ей '}' включительно
+    fn skip_braces(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.current_token() {
+                Token::КонецФайла => return,
+                Token::ЛевФигСкобка => depth += 1,
+                Token::ПравФигСкобка => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let open = self.current_span();
+        self.expect(Token::ЛевФигСкобка)?;
+
+        let mut statements = Vec::new();
+        while !matches!(self.current_token(), Token::ПравФигСкобка | Token::КонецФайла) {
+            if let Some(stmt) = self.parse_statement_or_recover() {
+                statements.push(stmt);
+            }
+        }
+
+        if self.current_token() == &Token::КонецФайла {
+            return Err(ParseError {
+                code: codes::UNCLOSED_BLOCK,
+                message: "Блок не закрыт: не хватает '}'".to_string(),
+                span: self.current_span(),
+                label: Some(Box::new(Label {
+                    span: open,
+                    message: "блок открыт здесь".to_string(),
+                })),
+                notes: Vec::new(),
+            });
+        }
+        self.advance();
+
+        Ok(statements)
+    }
+
+    fn parse_statement(&mut self) -> Result<Stmt, ParseError> {
         match self.current_token() {
             Token::Пусть => self.parse_variable_declaration(),
             Token::Если => self.parse_if_statement(),
+            Token::Выбор => self.parse_match_statement(),
+            Token::Попытка => self.parse_try_statement(),
             Token::Пока => self.parse_while_statement(),
-            Token::Функция => self.parse_function_declaration(),
+            Token::Для => self.parse_for_statement(),
+            // `фн(x) { ... }(5);` — анонимная функция в начале инструкции-выражения
+            Token::Функция if self.peek_token(1) != &Token::ЛевСкобка => self.parse_function_declaration(),
+            Token::Структура => self.parse_struct_declaration(),
+            Token::Класс => self.parse_class_declaration(),
             Token::Вернуть => self.parse_return_statement(),
+            Token::Бросить => self.parse_throw_statement(),
+            Token::Подключить => Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                "Подключать файлы можно только на верхнем уровне программы, не внутри блока".to_string(),
+            )),
+            // Внутри выражения '{' всегда открывает словарь, а блок бывает
+            // только после заголовка 'если', 'пока', 'для' или функции
+            Token::ЛевФигСкобка => Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                "Блок '{ ... }' может идти только после 'если', 'иначе', 'пока', 'для', 'попытка' или объявления функции".to_string(),
+            )),
+            Token::Прервать | Token::Продолжить => {
+                let start = self.current_span();
+                let kind = if self.current_token() == &Token::Прервать {
+                    StmtKind::Прервать
+                } else {
+                    StmtKind::Продолжить
+                };
+                self.advance();
+                self.expect(Token::ТочкаЗапятая)?;
+                Ok(Stmt::new(kind, self.span_from(start)))
+            }
             _ => {
-                let expr = self.parse_expression();
-                self.expect(Token::ТочкаЗапятая);
-                Stmt::Выражение(expr)
+                let start = self.current_span();
+                let expr = self.parse_expression()?;
+                self.expect(Token::ТочкаЗапятая)?;
+                Ok(Stmt::new(StmtKind::Выражение(expr), self.span_from(start)))
             }
         }
     }
-    
-    fn parse_variable_declaration(&mut self) -> Stmt {
-        self.expect(Token::Пусть);
-        
-        let имя = match self.current_token() {
-            Token::Идентификатор(name) => name.clone(),
-            _ => panic!("Ожидался идентификатор"),
+
+    fn parse_variable_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Пусть)?;
+
+        let имя = self.expect_identifier("имя переменной")?;
+        let тип = self.parse_optional_annotation()?;
+
+        self.expect(Token::Присвоить)?;
+
+        let значение = self.parse_expression()?;
+        self.expect(Token::ТочкаЗапятая)?;
+
+        Ok(Stmt::new(
+            StmtKind::ОбъявлениеПеременной { имя, тип, значение },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Если)?;
+        self.expect(Token::ЛевСкобка)?;
+        let условие = self.parse_expression()?;
+        self.expect(Token::ПравСкобка)?;
+
+        let тогда = self.parse_block()?;
+
+        // `иначе если (...) { ... }` — то же, что `иначе { если (...) { ... } }`
+        let иначе = if self.current_token() == &Token::Иначе {
+            self.advance();
+            if self.current_token() == &Token::Если {
+                Some(vec![self.parse_if_statement()?])
+            } else {
+                Some(self.parse_block()?)
+            }
+        } else {
+            None
         };
-        self.advance();
-        
-        self.expect(Token::Присвоить);
-        
-        let значение = self.parse_expression();
-        self.expect(Token::ТочкаЗапятая);
-        
-        Stmt::ОбъявлениеПеременной { имя, значение }
+
+        Ok(Stmt::new(StmtKind::Если { условие, тогда, иначе }, self.span_from(start)))
     }
-    
-    fn parse_if_statement(&mut self) -> Stmt {
-        self.expect(Token::Если);
-        self.expect(Token::ЛевСкобка);
-        let условие = self.parse_expression();
-        self.expect(Token::ПравСкобка);
-        
-        self.expect(Token::ЛевФигСкобка);
-        let mut тогда = Vec::new();
-        while self.current_token() != &Token::ПравФигСкобка {
-            тогда.push(self.parse_statement());
+
+    // `попытка { ... } перехват (ошибка) { ... } наконец { ... }`
+    fn parse_try_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Попытка)?;
+        let тело = self.parse_block()?;
+
+        let перехват = if self.current_token() == &Token::Перехват {
+            self.advance();
+            self.expect(Token::ЛевСкобка)?;
+            let span = self.current_span();
+            let name = self.expect_identifier("имя переменной для ошибки")?;
+            self.expect(Token::ПравСкобка)?;
+            let body = self.parse_block()?;
+            Some(Catch { name, span, body })
+        } else {
+            None
+        };
+        let наконец = if self.current_token() == &Token::Наконец {
+            self.advance();
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+
+        if перехват.is_none() && наконец.is_none() {
+            return Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                format!("После блока 'попытка' ожидается 'перехват' или 'наконец', но найдено: {}", self.current_token()),
+            ));
         }
-        self.expect(Token::ПравФигСкобка);
-        
-        let иначе = if self.current_token() == &Token::Иначе {
+
+        Ok(Stmt::new(StmtKind::Попытка { тело, перехват, наконец }, self.span_from(start)))
+    }
+
+    // `выбор (x) { случай 1, 2: ... случай "а": ... иначе: ... }`
+    fn parse_match_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Выбор)?;
+        self.expect(Token::ЛевСкобка)?;
+        let значение = self.parse_expression()?;
+        self.expect(Token::ПравСкобка)?;
+
+        let open = self.current_span();
+        self.expect(Token::ЛевФигСкобка)?;
+        let mut случаи = Vec::new();
+        let mut иначе = None;
+
+        loop {
+            match self.current_token() {
+                Token::ПравФигСкобка => break,
+                // Ветка с ошибкой в заголовке пропускается целиком, а разбор
+                // продолжается со следующей
+                Token::Случай if иначе.is_none() => {
+                    let case_start = self.current_span();
+                    self.advance();
+                    match self.parse_case_patterns() {
+                        Ok(patterns) => {
+                            let span = self.span_from(case_start);
+                            let body = self.parse_case_body()?;
+                            случаи.push(Case { patterns, body, span });
+                        }
+                        Err(err) => {
+                            self.errors.push(err);
+                            self.skip_case();
+                        }
+                    }
+                }
+                Token::Иначе if иначе.is_none() => {
+                    self.advance();
+                    match self.expect(Token::Двоеточие) {
+                        Ok(_) => иначе = Some(self.parse_case_body()?),
+                        Err(err) => {
+                            self.errors.push(err);
+                            self.skip_case();
+                        }
+                    }
+                }
+                Token::Случай | Token::Иначе => {
+                    let err = self.error(
+                        codes::UNEXPECTED_TOKEN,
+                        "Ветка 'иначе' должна быть последней в выборе".to_string(),
+                    );
+                    self.errors.push(err);
+                    self.advance();
+                    self.skip_case();
+                }
+                Token::КонецФайла => {
+                    return Err(ParseError {
+                        code: codes::UNCLOSED_BLOCK,
+                        message: "Выбор не закрыт: не хватает '}'".to_string(),
+                        span: self.current_span(),
+                        label: Some(Box::new(Label {
+                            span: open,
+                            message: "выбор открыт здесь".to_string(),
+                        })),
+                        notes: Vec::new(),
+                    });
+                }
+                other => {
+                    let err = self.error(
+                        codes::UNEXPECTED_TOKEN,
+                        format!("Ожидается 'случай' или 'иначе', но найдено: {}", other),
+                    );
+                    self.errors.push(err);
+                    self.skip_case();
+                }
+            }
+        }
+        self.expect(Token::ПравФигСкобка)?;
+
+        Ok(Stmt::new(StmtKind::Выбор { значение, случаи, иначе }, self.span_from(start)))
+    }
+
+    // Образцы ветки через запятую и двоеточие после них
+    fn parse_case_patterns(&mut self) -> Result<Vec<Pattern>, ParseError> {
+        let mut patterns = vec![self.parse_pattern()?];
+        while self.current_token() == &Token::Запятая {
             self.advance();
-            self.expect(Token::ЛевФигСкобка);
-            let mut else_block = Vec::new();
-            while self.current_token() != &Token::ПравФигСкобка {
-                else_block.push(self.parse_statement());
+            patterns.push(self.parse_pattern()?);
+        }
+        self.expect(Token::Двоеточие)?;
+        Ok(patterns)
+    }
+
+    // Пропускает испорченную ветку выбора до следующей ветки или конца выбора
+    fn skip_case(&mut self) {
+        loop {
+            match self.current_token() {
+                Token::Случай | Token::Иначе | Token::ПравФигСкобка | Token::КонецФайла => return,
+                Token::ЛевФигСкобка => self.skip_braces(),
+                _ => self.advance(),
             }
-            self.expect(Token::ПравФигСкобка);
-            Some(else_block)
+        }
+    }
+
+    // Тело ветки: блок `{ ... }` или инструкции до следующей ветки
+    fn parse_case_body(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        if self.current_token() == &Token::ЛевФигСкобка {
+            return self.parse_block();
+        }
+        let mut body = Vec::new();
+        while !matches!(
+            self.current_token(),
+            Token::Случай | Token::Иначе | Token::ПравФигСкобка | Token::КонецФайла
+        ) {
+            if let Some(stmt) = self.parse_statement_or_recover() {
+                body.push(stmt);
+            }
+        }
+        Ok(body)
+    }
+
+    // Образец: число, строка, `истина`, `ложь`, диапазон `от 1 до 10`, имя,
+    // `_`, список `[x, y]` или структура `Точка { x, y: 0 }`
+    fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let start = self.current_span();
+        let kind = match self.current_token().clone() {
+            Token::Число(_) | Token::Минус => PatternKind::Число(self.parse_pattern_number()?),
+            Token::Строка(s) => {
+                self.advance();
+                PatternKind::Строка(s)
+            }
+            Token::Шаблон(_) => {
+                let mut err = self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    "В образце нельзя использовать подстановки '{...}'".to_string(),
+                );
+                err.notes.push("фигурные скобки в тексте записываются как '{{' и '}}'".to_string());
+                return Err(err);
+            }
+            Token::Истина | Token::Ложь => {
+                let value = self.current_token() == &Token::Истина;
+                self.advance();
+                PatternKind::Булево(value)
+            }
+            // `случай от:` — переменная с именем «от», а не диапазон
+            Token::Идентификатор(_)
+                if self.at_word("от") && matches!(self.peek_token(1), Token::Число(_) | Token::Минус) =>
+            {
+                self.advance();
+                let from = self.parse_pattern_number()?;
+                if !self.at_word("до") {
+                    return Err(self.error(
+                        codes::UNEXPECTED_TOKEN,
+                        format!("Ожидается 'до', но найдено: {}", self.current_token()),
+                    ));
+                }
+                self.advance();
+                let to = self.parse_pattern_number()?;
+                // Такой образец ни с чем не совпадёт, но разбор выбора можно продолжать
+                if from > to {
+                    self.errors.push(ParseError {
+                        code: codes::UNEXPECTED_TOKEN,
+                        message: format!("Пустой диапазон: начало {} больше конца {}", from, to),
+                        span: self.span_from(start),
+                        label: None,
+                        notes: vec![format!("наверное, имелось в виду: от {} до {}", to, from)],
+                    });
+                }
+                PatternKind::Диапазон(from, to)
+            }
+            Token::Идентификатор(name) => {
+                self.advance();
+                if self.current_token() == &Token::ЛевФигСкобка {
+                    self.advance();
+                    PatternKind::Структура { имя: name, поля: self.parse_field_patterns()? }
+                } else {
+                    PatternKind::Переменная(name)
+                }
+            }
+            Token::ЛевКвСкобка => {
+                self.advance();
+                let mut items = Vec::new();
+                while self.current_token() != &Token::ПравКвСкобка {
+                    items.push(self.parse_pattern()?);
+                    if self.current_token() == &Token::Запятая {
+                        self.advance();
+                    } else if self.current_token() != &Token::ПравКвСкобка {
+                        return Err(self.error(
+                            codes::UNEXPECTED_TOKEN,
+                            format!("Ожидается ',' или ']', но найдено: {}", self.current_token()),
+                        ));
+                    }
+                }
+                self.expect(Token::ПравКвСкобка)?;
+                PatternKind::Список(items)
+            }
+            other => {
+                return Err(self.error(
+                    codes::EXPECTED_EXPRESSION,
+                    format!(
+                        "Ожидается образец: число, строка, истина, ложь, диапазон, имя, список или структура, но найдено: {}",
+                        other
+                    ),
+                ))
+            }
+        };
+        Ok(Pattern { kind, span: self.span_from(start) })
+    }
+
+    // Число в образце, возможно со знаком минус
+    fn parse_pattern_number(&mut self) -> Result<f64, ParseError> {
+        let negative = self.current_token() == &Token::Минус;
+        if negative {
+            self.advance();
+        }
+        match self.current_token() {
+            Token::Число(n) => {
+                let n = *n;
+                self.advance();
+                Ok(if negative { -n } else { n })
+            }
+            other => Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                format!("В образце ожидается число, но найдено: {}", other),
+            )),
+        }
+    }
+
+    // Поля образца структуры после '{': `x` или `x: образец` через запятую
+    fn parse_field_patterns(&mut self) -> Result<Vec<FieldPattern>, ParseError> {
+        let mut fields = Vec::new();
+
+        while self.current_token() != &Token::ПравФигСкобка {
+            let span = self.current_span();
+            let name = self.expect_identifier("имя поля")?;
+            let pattern = if self.current_token() == &Token::Двоеточие {
+                self.advance();
+                self.parse_pattern()?
+            } else {
+                Pattern { kind: PatternKind::Переменная(name.clone()), span }
+            };
+            fields.push(FieldPattern { name, span, pattern });
+            if self.current_token() == &Token::Запятая {
+                self.advance();
+            } else if self.current_token() != &Token::ПравФигСкобка {
+                return Err(self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    format!("Ожидается ',' или '}}', но найдено: {}", self.current_token()),
+                ));
+            }
+        }
+        self.expect(Token::ПравФигСкобка)?;
+
+        Ok(fields)
+    }
+
+    fn parse_while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Пока)?;
+        self.expect(Token::ЛевСкобка)?;
+        let условие = self.parse_expression()?;
+        self.expect(Token::ПравСкобка)?;
+
+        let тело = self.parse_block()?;
+
+        Ok(Stmt::new(StmtKind::Пока { условие, тело }, self.span_from(start)))
+    }
+
+    // «от», «до», «шаг» и «в» значат что-то только внутри заголовка цикла,
+    // поэтому это не ключевые слова и ими можно называть переменные
+    fn parse_for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Для)?;
+        let переменная = self.expect_identifier("имя переменной цикла")?;
+
+        if self.at_word("в") {
+            self.advance();
+            let коллекция = self.parse_expression()?;
+            let тело = self.parse_block()?;
+            return Ok(Stmt::new(
+                StmtKind::ДляКаждого { переменная, коллекция, тело },
+                self.span_from(start),
+            ));
+        }
+
+        if !self.at_word("от") {
+            return Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                format!(
+                    "Ожидается 'от' или 'в' после имени переменной цикла, но найдено: {}",
+                    self.current_token()
+                ),
+            ));
+        }
+        self.advance();
+        let начало = self.parse_expression()?;
+
+        if !self.at_word("до") {
+            return Err(self.error(
+                codes::UNEXPECTED_TOKEN,
+                format!("Ожидается 'до', но найдено: {}", self.current_token()),
+            ));
+        }
+        self.advance();
+        let конец = self.parse_expression()?;
+
+        let шаг = if self.at_word("шаг") {
+            self.advance();
+            Some(self.parse_expression()?)
         } else {
             None
         };
-        
-        Stmt::Если { условие, тогда, иначе }
+
+        let тело = self.parse_block()?;
+        Ok(Stmt::new(
+            StmtKind::Для { переменная, начало, конец, шаг, тело },
+            self.span_from(start),
+        ))
     }
-    
-    fn parse_while_statement(&mut self) -> Stmt {
-        self.expect(Token::Пока);
-        self.expect(Token::ЛевСкобка);
-        let условие = self.parse_expression();
-        self.expect(Token::ПравСкобка);
-        
-        self.expect(Token::ЛевФигСкобка);
-        let mut тело = Vec::new();
+
+    fn parse_function_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Функция)?;
+
+        let имя = self.expect_identifier("имя функции")?;
+        let параметры = self.parse_params()?;
+        let тип_результата = self.parse_optional_annotation()?;
+
+        let тело = self.parse_block()?;
+
+        Ok(Stmt::new(
+            StmtKind::ОбъявлениеФункции { имя, параметры, тип_результата, тело },
+            self.span_from(start),
+        ))
+    }
+
+    fn parse_struct_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Структура)?;
+
+        let имя = self.expect_identifier("имя структуры")?;
+        self.expect(Token::ЛевФигСкобка)?;
+        let mut поля = Vec::new();
+
         while self.current_token() != &Token::ПравФигСкобка {
-            тело.push(self.parse_statement());
+            let start = self.current_span();
+            let name = self.expect_identifier("имя поля")?;
+            let ty = self.parse_optional_annotation()?;
+            поля.push(Param { name, ty, span: self.span_from(start) });
+
+            if self.current_token() == &Token::Запятая {
+                self.advance();
+            } else if self.current_token() != &Token::ПравФигСкобка {
+                return Err(self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    format!("Ожидается ',' или '}}', но найдено: {}", self.current_token()),
+                ));
+            }
         }
-        self.expect(Token::ПравФигСкобка);
-        
-        Stmt::Пока { условие, тело }
+        self.expect(Token::ПравФигСкобка)?;
+
+        Ok(Stmt::new(StmtKind::ОбъявлениеСтруктуры { имя, поля }, self.span_from(start)))
     }
-    
-    fn parse_function_declaration(&mut self) -> Stmt {
-        self.expect(Token::Функция);
-        
-        let имя = match self.current_token() {
-            Token::Идентификатор(name) => name.clone(),
-            _ => panic!("Ожидалось имя функции"),
+
+    // «наследует» значит что-то только после имени класса, поэтому это не ключевое слово
+    fn parse_class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Класс)?;
+
+        let имя = self.expect_identifier("имя класса")?;
+        let родитель = if self.at_word("наследует") {
+            self.advance();
+            let span = self.current_span();
+            Some((self.expect_identifier("имя класса-родителя")?, span))
+        } else {
+            None
         };
-        self.advance();
-        
-        self.expect(Token::ЛевСкобка);
+
+        let open = self.current_span();
+        self.expect(Token::ЛевФигСкобка)?;
+        let mut поля = Vec::new();
+        let mut методы = Vec::new();
+
+        loop {
+            match self.current_token() {
+                Token::ПравФигСкобка => break,
+                Token::Функция => {
+                    let start = self.current_span();
+                    self.advance();
+                    let name = self.expect_identifier("имя метода")?;
+                    let params = self.parse_params()?;
+                    let result = self.parse_optional_annotation()?;
+                    let body = self.parse_block()?;
+                    методы.push(Method {
+                        name,
+                        span: self.span_from(start),
+                        function: Lambda { params, result, body },
+                    });
+                }
+                Token::Идентификатор(_) => {
+                    let start = self.current_span();
+                    let name = self.expect_identifier("имя поля")?;
+                    let ty = self.parse_optional_annotation()?;
+                    let param = Param { name, ty, span: self.span_from(start) };
+                    let value = if self.current_token() == &Token::Присвоить {
+                        self.advance();
+                        Some(self.parse_expression()?)
+                    } else {
+                        None
+                    };
+                    self.expect(Token::ТочкаЗапятая)?;
+                    поля.push(ClassField { param, value });
+                }
+                Token::КонецФайла => {
+                    return Err(ParseError {
+                        code: codes::UNCLOSED_BLOCK,
+                        message: format!("Класс '{}' не закрыт: не хватает '}}'", имя),
+                        span: self.current_span(),
+                        label: Some(Box::new(Label {
+                            span: open,
+                            message: "класс открыт здесь".to_string(),
+                        })),
+                        notes: Vec::new(),
+                    });
+                }
+                other => {
+                    return Err(self.error(
+                        codes::UNEXPECTED_TOKEN,
+                        format!(
+                            "В классе ожидается поле (`имя = значение;`) или метод (`функция имя() {{ ... }}`), но найдено: {}",
+                            other
+                        ),
+                    ));
+                }
+            }
+        }
+        self.expect(Token::ПравФигСкобка)?;
+
+        Ok(Stmt::new(
+            StmtKind::ОбъявлениеКласса { имя, родитель, поля, методы },
+            self.span_from(start),
+        ))
+    }
+
+    // Список параметров в скобках: `(x: число, y)`
+    fn parse_params(&mut self) -> Result<Vec<Param>, ParseError> {
+        self.expect(Token::ЛевСкобка)?;
         let mut параметры = Vec::new();
-        
+
         while self.current_token() != &Token::ПравСкобка {
-            if let Token::Идентификатор(param) = self.current_token() {
-                параметры.push(param.clone());
+            let start = self.current_span();
+            let name = self.expect_identifier("имя параметра")?;
+            let ty = self.parse_optional_annotation()?;
+            параметры.push(Param { name, ty, span: self.span_from(start) });
+
+            if self.current_token() == &Token::Запятая {
                 self.advance();
-                
-                if self.current_token() == &Token::Запятая {
-                    self.advance();
+            } else if self.current_token() != &Token::ПравСкобка {
+                return Err(self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    format!("Ожидается ',' или ')', но найдено: {}", self.current_token()),
+                ));
+            }
+        }
+        self.expect(Token::ПравСкобка)?;
+
+        Ok(параметры)
+    }
+
+    // Необязательная аннотация типа `: число`
+    fn parse_optional_annotation(&mut self) -> Result<Option<TypeAnnotation>, ParseError> {
+        if self.current_token() != &Token::Двоеточие {
+            return Ok(None);
+        }
+        self.advance();
+
+        let span = self.current_span();
+        // `функция` и `структура` — ключевые слова, но и названия типов
+        let keyword_type = match self.current_token() {
+            Token::Функция => Some(Type::Функция),
+            Token::Структура => Some(Type::Структура),
+            _ => None,
+        };
+        if let Some(ty) = keyword_type {
+            self.advance();
+            return Ok(Some(TypeAnnotation { ty, span }));
+        }
+        let name = self.expect_identifier("название типа")?;
+        match Type::from_name(&name) {
+            Some(ty) => Ok(Some(TypeAnnotation { ty, span })),
+            None => {
+                let known: Vec<&str> = Type::ALL.iter().map(|ty| ty.name()).collect();
+                let mut err = ParseError {
+                    code: codes::UNKNOWN_TYPE,
+                    message: format!("Неизвестный тип '{}'", name),
+                    span,
+                    label: None,
+                    notes: vec![format!("доступные типы: {}", known.join(", "))],
+                };
+                if let Some(candidate) = suggest::closest(&name, known) {
+                    err.notes.push(suggest::did_you_mean(candidate));
                 }
-            } else {
-                break;
+                Err(err)
             }
         }
-        self.expect(Token::ПравСкобка);
-        
-        self.expect(Token::ЛевФигСкобка);
-        let mut тело = Vec::new();
-        while self.current_token() != &Token::ПравФигСкобка {
-            тело.push(self.parse_statement());
+    }
+
+    fn parse_return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Вернуть)?;
+        let expr = self.parse_expression()?;
+        self.expect(Token::ТочкаЗапятая)?;
+        Ok(Stmt::new(StmtKind::Вернуть(expr), self.span_from(start)))
+    }
+
+    fn parse_throw_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
+        self.expect(Token::Бросить)?;
+        let expr = self.parse_expression()?;
+        self.expect(Token::ТочкаЗапятая)?;
+        Ok(Stmt::new(StmtKind::Бросить(expr), self.span_from(start)))
+    }
+
+    fn parse_expression(&mut self) -> Result<Expr, ParseError> {
+        self.parse_or()
+    }
+
+    // «и», «или», `div` и `mod` — операторы только между операндами,
+    // а не ключевые слова, поэтому переменную по-прежнему можно назвать `и`
+    fn at_word(&self, word: &str) -> bool {
+        matches!(self.current_token(), Token::Идентификатор(name) if name == word)
+    }
+
+    fn at_logical(&self, token: Token, word: &str) -> bool {
+        self.at_word(word) || *self.current_token() == token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_and()?;
+
+        while self.at_logical(Token::Или, "или") {
+            self.advance();
+            let right = self.parse_and()?;
+            left = Self::binary(left, BinOp::Или, right);
         }
-        self.expect(Token::ПравФигСкобка);
-        
-        Stmt::ОбъявлениеФункции { имя, параметры, тело }
+
+        Ok(left)
     }
-    
-    fn parse_return_statement(&mut self) -> Stmt {
-        self.expect(Token::Вернуть);
-        let expr = self.parse_expression();
-        self.expect(Token::ТочкаЗапятая);
-        Stmt::Вернуть(expr)
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_comparison()?;
+
+        while self.at_logical(Token::И, "и") {
+            self.advance();
+            let right = self.parse_comparison()?;
+            left = Self::binary(left, BinOp::И, right);
+        }
+
+        Ok(left)
     }
-    
-    fn parse_expression(&mut self) -> Expr {
-        self.parse_comparison()
+
+    fn binary(left: Expr, op: BinOp, right: Expr) -> Expr {
+        let span = left.span.to(right.span);
+        Expr::new(
+            ExprKind::БинарнаяОперация {
+                левый: Box::new(left),
+                оператор: op,
+                правый: Box::new(right),
+            },
+            span,
+        )
     }
-    
-    fn parse_comparison(&mut self) -> Expr {
-        let mut left = self.parse_term();
-        
+
+    fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_term()?;
+
         while matches!(
             self.current_token(),
-            Token::Равно | Token::НеРавно | Token::Больше | 
+            Token::Равно | Token::НеРавно | Token::Больше |
             Token::Меньше | Token::БольшеРавно | Token::МеньшеРавно
         ) {
             let op = match self.current_token() {
@@ -178,21 +1014,17 @@ impl Parser {
                 _ => unreachable!(),
             };
             self.advance();
-            
-            let right = self.parse_term();
-            left = Expr::БинарнаяОперация {
-                левый: Box::new(left),
-                оператор: op,
-                правый: Box::new(right),
-            };
+
+            let right = self.parse_term()?;
+            left = Self::binary(left, op, right);
         }
-        
-        left
+
+        Ok(left)
     }
-    
-    fn parse_term(&mut self) -> Expr {
-        let mut left = self.parse_factor();
-        
+
+    fn parse_term(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_factor()?;
+
         while matches!(self.current_token(), Token::Плюс | Token::Минус) {
             let op = match self.current_token() {
                 Token::Плюс => BinOp::Плюс,
@@ -200,112 +1032,401 @@ impl Parser {
                 _ => unreachable!(),
             };
             self.advance();
-            
-            let right = self.parse_factor();
-            left = Expr::БинарнаяОперация {
-                левый: Box::new(left),
-                оператор: op,
-                правый: Box::new(right),
-            };
+
+            let right = self.parse_factor()?;
+            left = Self::binary(left, op, right);
         }
-        
-        left
+
+        Ok(left)
     }
-    
-    fn parse_factor(&mut self) -> Expr {
-        let mut left = self.parse_unary();
-        
-        while matches!(self.current_token(), Token::Умножить | Token::Разделить) {
+
+    fn parse_factor(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.parse_unary()?;
+
+        loop {
             let op = match self.current_token() {
                 Token::Умножить => BinOp::Умножить,
                 Token::Разделить => BinOp::Разделить,
-                _ => unreachable!(),
+                Token::Остаток => BinOp::Остаток,
+                _ if self.at_word("mod") => BinOp::Остаток,
+                _ if self.at_word("div") => BinOp::ЦелоеДеление,
+                _ => break,
             };
             self.advance();
-            
-            let right = self.parse_unary();
-            left = Expr::БинарнаяОперация {
-                левый: Box::new(left),
-                оператор: op,
-                правый: Box::new(right),
-            };
+
+            let right = self.parse_unary()?;
+            left = Self::binary(left, op, right);
         }
-        
-        left
+
+        Ok(left)
     }
-    
-    fn parse_unary(&mut self) -> Expr {
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
         match self.current_token() {
             Token::Минус => {
                 self.advance();
-                let expr = self.parse_unary();
-                Expr::БинарнаяОперация {
-                    левый: Box::new(Expr::Число(0.0)),
-                    оператор: BinOp::Минус,
-                    правый: Box::new(expr),
-                }
+                let expr = self.parse_unary()?;
+                let span = self.span_from(start);
+                Ok(Expr::new(
+                    ExprKind::БинарнаяОперация {
+                        левый: Box::new(Expr::new(ExprKind::Число(0.0), start)),
+                        оператор: BinOp::Минус,
+                        правый: Box::new(expr),
+                    },
+                    span,
+                ))
             }
             Token::Плюс => {
                 self.advance();
                 self.parse_unary()
             }
-            _ => self.parse_primary(),
+            Token::Не => {
+                self.advance();
+                let expr = self.parse_unary()?;
+                Ok(Expr::new(ExprKind::Отрицание(Box::new(expr)), self.span_from(start)))
+            }
+            _ => self.parse_power(),
         }
     }
-    
-    fn parse_primary(&mut self) -> Expr {
-        match self.current_token().clone() {
+
+    // Степень связывает сильнее унарного минуса (`-2^2` = -4)
+    // и вычисляется справа налево (`2^3^2` = 2^9)
+    fn parse_power(&mut self) -> Result<Expr, ParseError> {
+        let base = self.parse_postfix()?;
+        if self.current_token() != &Token::Степень {
+            return Ok(base);
+        }
+        self.advance();
+        let exponent = self.parse_unary()?;
+        Ok(Self::binary(base, BinOp::Степень, exponent))
+    }
+
+    // Обращение по индексу: `список[и]`, `матрица[и][к]`, `список[и] = значение`,
+    // к полю: `точка.x`, `точка.x = значение`, вызов метода: `собака.голос()`
+    // и вызов получившейся функции: `действия[0](x)`, `сделать(1)(2)`
+    fn parse_postfix(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            match self.current_token() {
+                Token::ЛевСкобка => {
+                    self.advance();
+                    let аргументы = self.parse_items(Token::ПравСкобка)?;
+                    let kind = ExprKind::Вызов { функция: Box::new(expr), аргументы };
+                    expr = Expr::new(kind, self.span_from(start));
+                    continue;
+                }
+                Token::Точка => {
+                    self.advance();
+                    let поле = self.expect_identifier("имя поля после '.'")?;
+                    if self.current_token() == &Token::ЛевСкобка {
+                        self.advance();
+                        let аргументы = self.parse_items(Token::ПравСкобка)?;
+                        let kind = ExprKind::ВызовМетода { объект: Box::new(expr), метод: поле, аргументы };
+                        expr = Expr::new(kind, self.span_from(start));
+                        continue;
+                    }
+                    if self.current_token() == &Token::Присвоить {
+                        self.advance();
+                        let значение = self.parse_expression()?;
+                        let kind = ExprKind::ПрисваиваниеПолю {
+                            объект: Box::new(expr),
+                            поле,
+                            значение: Box::new(значение),
+                        };
+                        return Ok(Expr::new(kind, self.span_from(start)));
+                    }
+                    let kind = ExprKind::Поле { объект: Box::new(expr), поле };
+                    expr = Expr::new(kind, self.span_from(start));
+                    continue;
+                }
+                Token::ЛевКвСкобка => self.advance(),
+                _ => break,
+            }
+            let индекс = self.parse_expression()?;
+            self.expect(Token::ПравКвСкобка)?;
+
+            if self.current_token() == &Token::Присвоить {
+                self.advance();
+                let значение = self.parse_expression()?;
+                let kind = ExprKind::ПрисваиваниеПоИндексу {
+                    объект: Box::new(expr),
+                    индекс: Box::new(индекс),
+                    значение: Box::new(значение),
+                };
+                return Ok(Expr::new(kind, self.span_from(start)));
+            }
+
+            let kind = ExprKind::Индекс { объект: Box::new(expr), индекс: Box::new(индекс) };
+            expr = Expr::new(kind, self.span_from(start));
+        }
+
+        Ok(expr)
+    }
+
+    // Выражение каждой подстановки разбирается отдельным парсером по её токенам
+    fn parse_template(&mut self, parts: Vec<StringPart>) -> Result<Vec<TemplatePart>, ParseError> {
+        let mut template = Vec::new();
+        for part in parts {
+            match part {
+                StringPart::Текст(text) => template.push(TemplatePart::Текст(text)),
+                StringPart::Подстановка(tokens) => {
+                    let mut parser = Parser::new(tokens);
+                    let expr = parser.parse_expression();
+                    self.errors.append(&mut parser.errors);
+                    let expr = expr?;
+                    if parser.current_token() != &Token::ПравФигСкобка {
+                        return Err(parser.error(
+                            codes::UNEXPECTED_TOKEN,
+                            format!("Ожидается '}}' в конце подстановки, но найдено: {}", parser.current_token()),
+                        ));
+                    }
+                    template.push(TemplatePart::Выражение(expr));
+                }
+            }
+        }
+        Ok(template)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
+        let kind = match self.current_token().clone() {
             Token::Число(n) => {
                 self.advance();
-                Expr::Число(n)
+                ExprKind::Число(n)
             }
             Token::Строка(s) => {
                 self.advance();
-                Expr::Строка(s)
+                ExprKind::Строка(s)
+            }
+            Token::Шаблон(parts) => {
+                self.advance();
+                ExprKind::Шаблон(self.parse_template(parts)?)
             }
             Token::Истина => {
                 self.advance();
-                Expr::Булево(true)
+                ExprKind::Булево(true)
             }
             Token::Ложь => {
                 self.advance();
-                Expr::Булево(false)
+                ExprKind::Булево(false)
+            }
+            Token::Ошибка => {
+                self.advance();
+                ExprKind::Ошибка
+            }
+            Token::Этот => {
+                self.advance();
+                ExprKind::Этот
             }
             Token::Идентификатор(name) => {
                 self.advance();
-                
+
                 // Проверка на вызов функции
                 if self.current_token() == &Token::ЛевСкобка {
                     self.advance();
-                    let mut аргументы = Vec::new();
-                    
-                    while self.current_token() != &Token::ПравСкобка {
-                        аргументы.push(self.parse_expression());
-                        if self.current_token() == &Token::Запятая {
-                            self.advance();
-                        }
-                    }
-                    self.expect(Token::ПравСкобка);
-                    
-                    Expr::ВызовФункции { имя: name, аргументы }
+                    let аргументы = self.parse_items(Token::ПравСкобка)?;
+                    ExprKind::ВызовФункции { имя: name, аргументы }
+                } else if self.at_struct_literal() {
+                    self.advance();
+                    ExprKind::СозданиеСтруктуры { имя: name, поля: self.parse_field_values()? }
                 } else if self.current_token() == &Token::Присвоить {
                     self.advance();
-                    let значение = self.parse_expression();
-                    Expr::Присваивание {
+                    let значение = self.parse_expression()?;
+                    ExprKind::Присваивание {
                         имя: name,
                         значение: Box::new(значение),
                     }
                 } else {
-                    Expr::Идентификатор(name)
+                    ExprKind::Идентификатор(name)
                 }
             }
             Token::ЛевСкобка => {
                 self.advance();
-                let expr = self.parse_expression();
-                self.expect(Token::ПравСкобка);
-                expr
+                let expr = self.parse_expression()?;
+                self.expect(Token::ПравСкобка)?;
+                return Ok(expr);
+            }
+            Token::ЛевКвСкобка => {
+                self.advance();
+                ExprKind::Список(self.parse_items(Token::ПравКвСкобка)?)
+            }
+            Token::ЛевФигСкобка => {
+                self.advance();
+                ExprKind::Словарь(self.parse_dict_entries()?)
+            }
+            Token::Функция => {
+                self.advance();
+                if let Token::Идентификатор(name) = self.current_token() {
+                    return Err(self.error(
+                        codes::UNEXPECTED_TOKEN,
+                        format!(
+                            "Функцию с именем '{}' можно объявить только отдельной инструкцией; у функции-значения нет имени: фн(x) {{ ... }}",
+                            name
+                        ),
+                    ));
+                }
+                let параметры = self.parse_params()?;
+                let тип_результата = self.parse_optional_annotation()?;
+                let тело = self.parse_block()?;
+                ExprKind::Лямбда(Box::new(Lambda { params: параметры, result: тип_результата, body: тело }))
+            }
+            other => {
+                return Err(self.error(
+                    codes::EXPECTED_EXPRESSION,
+                    format!("Ожидается выражение, но найдено: {}", other),
+                ))
+            }
+        };
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    // `Имя { поле: ...`: после имени структуры, а не блок цикла `для x в список { ... }`,
+    // потому что инструкция не может начинаться с `имя:`
+    fn at_struct_literal(&self) -> bool {
+        self.current_token() == &Token::ЛевФигСкобка
+            && matches!(self.peek_token(1), Token::Идентификатор(_))
+            && self.peek_token(2) == &Token::Двоеточие
+    }
+
+    // Поля `имя: значение` через запятую после '{' вплоть до '}'
+    fn parse_field_values(&mut self) -> Result<Vec<FieldValue>, ParseError> {
+        let mut fields = Vec::new();
+
+        while self.current_token() != &Token::ПравФигСкобка {
+            let span = self.current_span();
+            let name = self.expect_identifier("имя поля")?;
+            self.expect(Token::Двоеточие)?;
+            let value = self.parse_expression()?;
+            fields.push(FieldValue { name, span, value });
+            if self.current_token() == &Token::Запятая {
+                self.advance();
+            } else if self.current_token() != &Token::ПравФигСкобка {
+                return Err(self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    format!("Ожидается ',' или '}}', но найдено: {}", self.current_token()),
+                ));
             }
-            _ => panic!("Неожиданный токен: {:?}", self.current_token()),
         }
+        self.expect(Token::ПравФигСкобка)?;
+
+        Ok(fields)
     }
-}
\ No newline at end of file
+
+    // Пары `ключ: значение` через запятую после '{' вплоть до '}'
+    fn parse_dict_entries(&mut self) -> Result<Vec<(Expr, Expr)>, ParseError> {
+        let mut entries = Vec::new();
+
+        while self.current_token() != &Token::ПравФигСкобка {
+            let key = self.parse_expression()?;
+            self.expect(Token::Двоеточие)?;
+            let value = self.parse_expression()?;
+            entries.push((key, value));
+            if self.current_token() == &Token::Запятая {
+                self.advance();
+            } else if self.current_token() != &Token::ПравФигСкобка {
+                return Err(self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    format!("Ожидается ',' или '}}', но найдено: {}", self.current_token()),
+                ));
+            }
+        }
+        self.expect(Token::ПравФигСкобка)?;
+
+        Ok(entries)
+    }
+
+    // Выражения через запятую вплоть до `close`: аргументы вызова после '('
+    // или элементы списка после '['
+    fn parse_items(&mut self, close: Token) -> Result<Vec<Expr>, ParseError> {
+        let mut items = Vec::new();
+        let closing = if close == Token::ПравСкобка { ')' } else { ']' };
+
+        while self.current_token() != &close {
+            items.push(self.parse_expression()?);
+            if self.current_token() == &Token::Запятая {
+                self.advance();
+            } else if self.current_token() != &close {
+                return Err(self.error(
+                    codes::UNEXPECTED_TOKEN,
+                    format!("Ожидается ',' или '{}', но найдено: {}", closing, self.current_token()),
+                ));
+            }
+        }
+        self.expect(close)?;
+
+        Ok(items)
+    }
+}
+
+fn is_statement_start(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Пусть
+            | Token::Если
+            | Token::Выбор
+            | Token::Попытка
+            | Token::Пока
+            | Token::Для
+            | Token::Функция
+            | Token::Структура
+            | Token::Класс
+            | Token::Вернуть
+            | Token::Бросить
+            | Token::Подключить
+            | Token::Прервать
+            | Token::Продолжить
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+    
+    // Сообщения синтаксических ошибок программы без лексических ошибок
+    fn errors(code: &str) -> Vec<String> {
+        let (tokens, lex_errors) = Lexer::new(code.to_string()).tokenize();
+        assert!(lex_errors.is_empty(), "лексические ошибки: {:?}", lex_errors);
+        let (_, errors) = Parser::new(tokens).parse();
+        errors.into_iter().map(|err| err.message).collect()
+    }
+    
+    #[test]
+    fn reports_every_broken_statement() {
+        let errors = errors("пусть x = ;\nпечать(1 +);\nпусть = 2;\nпечать(x);");
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+    }
+    
+    #[test]
+    fn recovery_skips_the_block_of_a_broken_statement() {
+        assert_eq!(
+            errors("есл (истина) { печать(1); }"),
+            ["Пропущена точка с запятой ';', дальше идёт: открывающая фигурная скобка '{'"]
+        );
+        assert_eq!(errors("есл (истина) { печать(1); } иначе { печать(2); }\nпечать(3);").len(), 1);
+        assert_eq!(errors("пусть = { 1: 2 };\nпечать(1);").len(), 1);
+    }
+    
+    #[test]
+    fn empty_range_pattern_is_reported_once() {
+        assert_eq!(
+            errors("выбор (5) { случай от 10 до 1: печать(\"a\"); иначе: печать(\"c\"); }"),
+            ["Пустой диапазон: начало 10 больше конца 1"]
+        );
+    }
+    
+    #[test]
+    fn broken_case_does_not_break_the_whole_match() {
+        let found = errors("выбор (5) { случай +: печать(1); случай 5: печать(2); иначе: печать(3); }\nпечать(4);");
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert!(found[0].starts_with("Ожидается образец"));
+        
+        assert_eq!(
+            errors("выбор (5) { иначе: печать(1); случай 1: { печать(2); } }\nпечать(4);"),
+            ["Ветка 'иначе' должна быть последней в выборе"]
+        );
+    }
+}
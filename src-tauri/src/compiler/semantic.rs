@@ -0,0 +1,1070 @@
+//! Статическая проверка программы до запуска: неизвестные имена,
+//! число аргументов, `вернуть` вне функции, `прервать` и `продолжить`
+//! вне цикла, `этот` вне метода, наследование и недостижимый код.
+
+use crate::compiler::ast::*;
+use crate::compiler::diagnostics::{codes, Diagnostic, Label, Stage};
+use crate::compiler::interpreter::{BUILTINS, CONSTRUCTOR, ERROR_FIELDS, ERROR_STRUCT};
+use crate::compiler::lexer::Span;
+use crate::compiler::modules::Module;
+use crate::compiler::suggest;
+use std::collections::{HashMap, HashSet};
+
+// Сколько аргументов принимает встроенная функция: (минимум, максимум)
+fn builtin_arity(name: &str) -> Option<(usize, Option<usize>)> {
+    match name {
+        "печать" => Some((0, None)),
+        "график" => Some((3, Some(3))),
+        "син" | "кос" | "эксп" | "лог" | "корень" | "длина" => Some((1, Some(1))),
+        "ключи" | "значения" => Some((1, Some(1))),
+        "добавить" | "удалить" | "содержит" => Some((2, Some(2))),
+        "вставить" => Some((3, Some(3))),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FunctionInfo {
+    params: usize,
+    span: Span,
+}
+
+// Имя в области видимости: переменная (параметр) или объявленная функция
+#[derive(Clone, Copy)]
+enum Binding {
+    Variable,
+    Function(FunctionInfo),
+}
+
+#[derive(Clone)]
+struct StructInfo {
+    fields: Vec<String>,
+    // У встроенной структуры `Ошибка` места объявления нет
+    span: Option<Span>,
+}
+
+#[derive(Clone)]
+struct ClassInfo {
+    parent: Option<String>,
+    // Собственные поля, без родительских
+    fields: Vec<String>,
+    // Число параметров собственного конструктора и где он объявлен
+    constructor: Option<(usize, Span)>,
+    span: Span,
+}
+
+struct Checker {
+    structs: HashMap<String, StructInfo>,
+    classes: HashMap<String, ClassInfo>,
+    // Переменные, объявленные где угодно на верхнем уровне программы:
+    // функция видит их, даже если объявлена раньше них
+    globals: HashSet<String>,
+    // Вложенные области видимости, внутренняя — последняя
+    scopes: Vec<HashMap<String, Binding>>,
+    in_function: bool,
+    // Внутри метода класса, где виден `этот`
+    in_method: bool,
+    // Сколько циклов вокруг текущей инструкции внутри текущей функции
+    loop_depth: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// Объявления верхнего уровня модуля, которые можно подключить
+#[derive(Default)]
+struct Exports {
+    bindings: HashMap<String, Binding>,
+    structs: HashMap<String, StructInfo>,
+    classes: HashMap<String, ClassInfo>,
+}
+
+/// Проверяет программу, не выполняя её.
+pub fn check(program: &Program) -> Vec<Diagnostic> {
+    let mut checker = Checker::new(program);
+    checker.check_program(program);
+    checker.diagnostics
+}
+
+/// Проверяет программу из нескольких файлов: каждый модуль видит свои
+/// объявления и подключённые имена.
+pub fn check_modules(modules: &[Module]) -> Vec<Diagnostic> {
+    let mut exports: Vec<Exports> = Vec::with_capacity(modules.len());
+    let mut diagnostics = Vec::new();
+    for module in modules {
+        let mut checker = Checker::new(&module.program);
+        let mut imported = HashMap::new();
+        for (index, name) in &module.imports {
+            let from = &exports[*index];
+            if let Some(binding) = from.bindings.get(name) {
+                imported.insert(name.clone(), *binding);
+            }
+            if let Some(info) = from.structs.get(name) {
+                checker.structs.insert(name.clone(), info.clone());
+            }
+            if let Some(info) = from.classes.get(name) {
+                checker.classes.insert(name.clone(), info.clone());
+            }
+        }
+        // Подключённые имена — самая внешняя область модуля
+        checker.scopes.push(imported);
+        checker.check_program(&module.program);
+        exports.push(checker.exports(&module.program));
+        diagnostics.extend(checker.diagnostics);
+    }
+    diagnostics
+}
+
+/// Блок, после которого выполнение не продолжится.
+pub fn always_returns(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Вернуть(_) | StmtKind::Бросить(_) => true,
+        StmtKind::Если { тогда, иначе: Some(иначе), .. } => {
+            always_returns(тогда) && always_returns(иначе)
+        }
+        StmtKind::Выбор { случаи, иначе: Some(иначе), .. } => {
+            случаи.iter().all(|case| always_returns(&case.body)) && always_returns(иначе)
+        }
+        StmtKind::Попытка { тело, перехват, наконец } => {
+            наконец.as_deref().is_some_and(always_returns)
+                || (always_returns(тело) && перехват.as_ref().is_none_or(|catch| always_returns(&catch.body)))
+        }
+        _ => false,
+    })
+}
+
+// Блок, из которого выполнение уходит: `вернуть`, `прервать`, `продолжить` или `бросить`
+fn leaves_block(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Вернуть(_) | StmtKind::Прервать | StmtKind::Продолжить | StmtKind::Бросить(_) => true,
+        StmtKind::Если { тогда, иначе: Some(иначе), .. } => {
+            leaves_block(тогда) && leaves_block(иначе)
+        }
+        StmtKind::Выбор { случаи, иначе: Some(иначе), .. } => {
+            случаи.iter().all(|case| leaves_block(&case.body)) && leaves_block(иначе)
+        }
+        StmtKind::Попытка { тело, перехват, наконец } => {
+            наконец.as_deref().is_some_and(leaves_block)
+                || (leaves_block(тело) && перехват.as_ref().is_none_or(|catch| leaves_block(&catch.body)))
+        }
+        _ => false,
+    })
+}
+
+impl Checker {
+    fn new(program: &Program) -> Self {
+        Checker {
+            structs: HashMap::from([(
+                ERROR_STRUCT.to_string(),
+                StructInfo { fields: ERROR_FIELDS.iter().map(|name| name.to_string()).collect(), span: None },
+            )]),
+            classes: HashMap::new(),
+            globals: program
+                .statements
+                .iter()
+                .filter_map(|stmt| match &stmt.kind {
+                    StmtKind::ОбъявлениеПеременной { имя, .. } => Some(имя.clone()),
+                    _ => None,
+                })
+                .collect(),
+            scopes: Vec::new(),
+            in_function: false,
+            in_method: false,
+            loop_depth: 0,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn check_program(&mut self, program: &Program) {
+        self.collect_types(&program.statements);
+        self.check_scoped_block(&program.statements, HashSet::new());
+    }
+
+    // Объявления верхнего уровня проверенного модуля. Класс отдаётся вместе
+    // с полями и конструктором родителей: родителя подключивший может не видеть.
+    fn exports(&self, program: &Program) -> Exports {
+        let mut exports = Exports::default();
+        for stmt in &program.statements {
+            match &stmt.kind {
+                StmtKind::ОбъявлениеПеременной { имя, .. } => {
+                    exports.bindings.insert(имя.clone(), Binding::Variable);
+                }
+                StmtKind::ОбъявлениеФункции { имя, параметры, .. } => {
+                    let info = FunctionInfo { params: параметры.len(), span: stmt.span };
+                    exports.bindings.insert(имя.clone(), Binding::Function(info));
+                }
+                StmtKind::ОбъявлениеСтруктуры { имя, поля } => {
+                    let fields = поля.iter().map(|field| field.name.clone()).collect();
+                    exports.structs.insert(имя.clone(), StructInfo { fields, span: Some(stmt.span) });
+                }
+                StmtKind::ОбъявлениеКласса { имя, .. } => {
+                    let fields = self
+                        .ancestry(имя)
+                        .iter()
+                        .rev()
+                        .flat_map(|class| self.classes[*class].fields.iter().cloned())
+                        .collect();
+                    let constructor = self.constructor(имя);
+                    exports.classes.insert(
+                        имя.clone(),
+                        ClassInfo { parent: None, fields, constructor, span: stmt.span },
+                    );
+                }
+                _ => {}
+            }
+        }
+        exports
+    }
+
+    // Структуры и классы видны из любого места программы, поэтому собираем их заранее
+    fn collect_types(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::ОбъявлениеФункции { тело, .. } => self.collect_types(тело),
+                StmtKind::ОбъявлениеСтруктуры { имя, поля } => {
+                    let fields = поля.iter().map(|field| field.name.clone()).collect();
+                    self.structs.insert(имя.clone(), StructInfo { fields, span: Some(stmt.span) });
+                }
+                StmtKind::ОбъявлениеКласса { имя, родитель, поля, методы } => {
+                    let constructor = методы
+                        .iter()
+                        .find(|method| method.name == CONSTRUCTOR)
+                        .map(|method| (method.function.params.len(), method.span));
+                    let parent = родитель.as_ref().map(|(name, _)| name.clone());
+                    let fields = поля.iter().map(|field| field.param.name.clone()).collect();
+                    self.classes.insert(
+                        имя.clone(),
+                        ClassInfo { parent, fields, constructor, span: stmt.span },
+                    );
+                }
+                StmtKind::Если { тогда, иначе, .. } => {
+                    self.collect_types(тогда);
+                    if let Some(иначе) = иначе {
+                        self.collect_types(иначе);
+                    }
+                }
+                StmtKind::Выбор { случаи, иначе, .. } => {
+                    for case in случаи {
+                        self.collect_types(&case.body);
+                    }
+                    if let Some(иначе) = иначе {
+                        self.collect_types(иначе);
+                    }
+                }
+                StmtKind::Попытка { тело, перехват, наконец } => {
+                    self.collect_types(тело);
+                    if let Some(catch) = перехват {
+                        self.collect_types(&catch.body);
+                    }
+                    if let Some(наконец) = наконец {
+                        self.collect_types(наконец);
+                    }
+                }
+                StmtKind::Пока { тело, .. }
+                | StmtKind::Для { тело, .. }
+                | StmtKind::ДляКаждого { тело, .. } => self.collect_types(тело),
+                _ => {}
+            }
+        }
+    }
+
+    // Ближайшее видимое объявление имени
+    fn lookup(&self, name: &str) -> Option<Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    fn is_declared(&self, name: &str) -> bool {
+        self.lookup(name).is_some()
+    }
+
+    fn declare(&mut self, name: &str) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), Binding::Variable);
+    }
+
+    // Видимые имена функций или, при `functions == false`, переменных
+    fn names(&self, functions: bool) -> impl Iterator<Item = &str> {
+        self.scopes
+            .iter()
+            .flatten()
+            .filter(move |(_, binding)| matches!(binding, Binding::Function(_)) == functions)
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn variable_names(&self) -> impl Iterator<Item = &str> {
+        self.names(false)
+    }
+
+    // Блок со своей областью видимости; `scope` — объявленные в ней заранее имена.
+    // Функции блока видны во всём блоке, в том числе выше своего объявления,
+    // но не снаружи: так же их объявляет и интерпретатор.
+    fn check_scoped_block(&mut self, stmts: &[Stmt], scope: HashSet<String>) {
+        let mut scope: HashMap<String, Binding> =
+            scope.into_iter().map(|name| (name, Binding::Variable)).collect();
+        for stmt in stmts {
+            if let StmtKind::ОбъявлениеФункции { имя, параметры, .. } = &stmt.kind {
+                let info = FunctionInfo { params: параметры.len(), span: stmt.span };
+                scope.insert(имя.clone(), Binding::Function(info));
+            }
+        }
+        self.scopes.push(scope);
+        self.check_block(stmts);
+        self.scopes.pop();
+    }
+
+    // Всё, что можно вызвать по имени: функции и конструкторы структур и классов
+    fn function_names(&self) -> impl Iterator<Item = &str> {
+        BUILTINS
+            .iter()
+            .copied()
+            .chain(self.names(true))
+            .chain(self.structs.keys().map(String::as_str))
+            .chain(self.classes.keys().map(String::as_str))
+    }
+
+    // Классы от `name` вверх по родителям; на цикле наследования останавливается
+    fn ancestry(&self, name: &str) -> Vec<&str> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = self.classes.get_key_value(name);
+        while let Some((name, info)) = current {
+            if chain.contains(&name.as_str()) {
+                break;
+            }
+            chain.push(name);
+            current = info.parent.as_ref().and_then(|parent| self.classes.get_key_value(parent));
+        }
+        chain
+    }
+
+    // Конструктор класса: свой или ближайшего родителя; `None` — конструктора нет
+    fn constructor(&self, class: &str) -> Option<(usize, Span)> {
+        self.ancestry(class)
+            .into_iter()
+            .find_map(|name| self.classes[name].constructor)
+    }
+
+    fn error(&mut self, code: &str, message: String, span: Span) -> &mut Diagnostic {
+        self.diagnostics
+            .push(Diagnostic::error(Stage::Semantic, code, message).with_span(span));
+        self.diagnostics.last_mut().unwrap()
+    }
+
+    fn check_block(&mut self, stmts: &[Stmt]) {
+        for (i, stmt) in stmts.iter().enumerate() {
+            self.check_statement(stmt);
+
+            if i + 1 < stmts.len() && leaves_block(std::slice::from_ref(stmt)) {
+                let span = stmts[i + 1].span.to(stmts[stmts.len() - 1].span);
+                let message = match stmt.kind {
+                    StmtKind::Прервать => "Код после 'прервать' никогда не выполнится",
+                    StmtKind::Продолжить => "Код после 'продолжить' никогда не выполнится",
+                    StmtKind::Бросить(_) => "Код после 'бросить' никогда не выполнится",
+                    _ => "Код после 'вернуть' никогда не выполнится",
+                };
+                self.diagnostics.push(
+                    Diagnostic::warning(Stage::Semantic, codes::UNREACHABLE_CODE, message)
+                    .with_span(span)
+                    .with_label(stmt.span, "здесь выполнение блока заканчивается"),
+                );
+                for rest in &stmts[i + 1..] {
+                    self.check_statement(rest);
+                }
+                return;
+            }
+        }
+    }
+
+    fn check_statement(&mut self, stmt: &Stmt) {
+        match &stmt.kind {
+            StmtKind::ОбъявлениеПеременной { имя, значение, .. } => {
+                // Анонимная функция может вызывать саму себя через переменную
+                let recursive = matches!(значение.kind, ExprKind::Лямбда(_));
+                if recursive {
+                    self.declare(имя);
+                }
+                self.check_expression(значение);
+                self.declare(имя);
+            }
+            StmtKind::Если { условие, тогда, иначе } => {
+                self.check_expression(условие);
+                self.check_scoped_block(тогда, HashSet::new());
+                if let Some(иначе) = иначе {
+                    self.check_scoped_block(иначе, HashSet::new());
+                }
+            }
+            StmtKind::Выбор { значение, случаи, иначе } => {
+                self.check_expression(значение);
+                for case in случаи {
+                    let scope = self.check_case_patterns(&case.patterns);
+                    self.check_scoped_block(&case.body, scope);
+                }
+                if let Some(иначе) = иначе {
+                    self.check_scoped_block(иначе, HashSet::new());
+                }
+            }
+            StmtKind::Попытка { тело, перехват, наконец } => {
+                self.check_scoped_block(тело, HashSet::new());
+                if let Some(catch) = перехват {
+                    self.check_scoped_block(&catch.body, HashSet::from([catch.name.clone()]));
+                }
+                if let Some(наконец) = наконец {
+                    self.check_scoped_block(наконец, HashSet::new());
+                }
+            }
+            StmtKind::Бросить(expr) => self.check_expression(expr),
+            StmtKind::Пока { условие, тело } => {
+                self.check_expression(условие);
+                self.loop_depth += 1;
+                self.check_scoped_block(тело, HashSet::new());
+                self.loop_depth -= 1;
+            }
+            StmtKind::Для { переменная, начало, конец, шаг, тело } => {
+                self.check_expression(начало);
+                self.check_expression(конец);
+                if let Some(шаг) = шаг {
+                    self.check_expression(шаг);
+                }
+                self.check_loop_body(переменная, тело);
+            }
+            StmtKind::ДляКаждого { переменная, коллекция, тело } => {
+                self.check_expression(коллекция);
+                self.check_loop_body(переменная, тело);
+            }
+            StmtKind::Вернуть(expr) => {
+                if !self.in_function {
+                    self.error(
+                        codes::RETURN_OUTSIDE_FUNCTION,
+                        "'вернуть' можно использовать только внутри функции".to_string(),
+                        stmt.span,
+                    );
+                }
+                self.check_expression(expr);
+            }
+            StmtKind::Прервать | StmtKind::Продолжить => {
+                if self.loop_depth == 0 {
+                    let word = if matches!(stmt.kind, StmtKind::Прервать) { "прервать" } else { "продолжить" };
+                    self.error(
+                        codes::JUMP_OUTSIDE_LOOP,
+                        format!("'{}' можно использовать только внутри цикла", word),
+                        stmt.span,
+                    );
+                }
+            }
+            StmtKind::Выражение(Expr { kind: ExprKind::Присваивание { имя, значение }, span }) => {
+                self.check_assignment(имя, значение, *span, true);
+            }
+            StmtKind::Выражение(expr) => self.check_expression(expr),
+            StmtKind::ОбъявлениеФункции { параметры, тело, .. } => {
+                // Функция видит переменные снаружи, в том числе глобальные,
+                // объявленные после неё
+                let mut scope = self.globals.clone();
+                scope.extend(параметры.iter().map(|param| param.name.clone()));
+                self.check_function_body(scope, тело);
+            }
+            StmtKind::ОбъявлениеСтруктуры { поля, .. } => self.check_fields(поля.iter()),
+            StmtKind::ОбъявлениеКласса { имя, родитель, поля, методы } => {
+                if let Some((parent, span)) = родитель {
+                    self.check_parent(имя, parent, *span, stmt.span);
+                }
+                self.check_fields(поля.iter().map(|field| &field.param));
+                for field in поля {
+                    if let Some(value) = &field.value {
+                        self.check_expression(value);
+                    }
+                }
+
+                let mut seen = HashSet::new();
+                for method in методы {
+                    if !seen.insert(method.name.as_str()) {
+                        self.error(
+                            codes::STRUCT_FIELDS,
+                            format!("Метод '{}' объявлен дважды", method.name),
+                            method.span,
+                        );
+                    } else if поля.iter().any(|field| field.param.name == method.name) {
+                        self.error(
+                            codes::STRUCT_FIELDS,
+                            format!("У класса '{}' уже есть поле '{}'", имя, method.name),
+                            method.span,
+                        );
+                    }
+
+                    let mut scope = self.globals.clone();
+                    scope.extend(method.function.params.iter().map(|param| param.name.clone()));
+                    let was_in_method = std::mem::replace(&mut self.in_method, true);
+                    self.check_function_body(scope, &method.function.body);
+                    self.in_method = was_in_method;
+                }
+            }
+        }
+    }
+
+    // Образцы одного случая; возвращает объявленные ими имена. Тело выполняется
+    // после любого из образцов, поэтому все они должны объявлять одни и те же имена.
+    fn check_case_patterns(&mut self, patterns: &[Pattern]) -> HashSet<String> {
+        let mut names: Option<HashSet<String>> = None;
+        for pattern in patterns {
+            self.check_pattern(pattern);
+
+            let bindings = pattern.bindings();
+            let mut own = HashSet::new();
+            for (name, span) in &bindings {
+                if !own.insert(name.to_string()) {
+                    self.error(
+                        codes::PATTERN,
+                        format!("Имя '{}' встречается в образце дважды", name),
+                        *span,
+                    );
+                }
+            }
+            match &names {
+                Some(first) if *first != own => {
+                    let mut expected: Vec<&str> = first.iter().map(String::as_str).collect();
+                    expected.sort();
+                    let listed = if expected.is_empty() { "никаких".to_string() } else { expected.join(", ") };
+                    self.error(
+                        codes::PATTERN,
+                        "Образцы одного случая должны объявлять одни и те же имена".to_string(),
+                        pattern.span,
+                    )
+                    .notes
+                    .push(format!("первый образец объявляет: {}", listed));
+                }
+                Some(_) => {}
+                None => names = Some(own),
+            }
+        }
+        names.unwrap_or_default()
+    }
+
+    fn check_pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Список(items) => {
+                for item in items {
+                    self.check_pattern(item);
+                }
+            }
+            PatternKind::Структура { имя, поля } => {
+                for field in поля {
+                    self.check_pattern(&field.pattern);
+                }
+                let known: Option<Vec<&str>> = if let Some(info) = self.structs.get(имя) {
+                    Some(info.fields.iter().map(String::as_str).collect())
+                } else if self.classes.contains_key(имя) {
+                    let ancestry = self.ancestry(имя);
+                    Some(
+                        ancestry
+                            .iter()
+                            .rev()
+                            .flat_map(|class| self.classes[*class].fields.iter().map(String::as_str))
+                            .collect(),
+                    )
+                } else {
+                    None
+                };
+                let Some(known) = known else {
+                    let hint = suggest::closest(
+                        имя,
+                        self.structs.keys().chain(self.classes.keys()).map(String::as_str),
+                    )
+                    .map(suggest::did_you_mean);
+                    self.error(
+                        codes::UNDECLARED_STRUCT,
+                        format!("Структура или класс '{}' не объявлены", имя),
+                        pattern.span,
+                    )
+                    .notes
+                    .extend(hint);
+                    return;
+                };
+                let mut problems = Vec::new();
+                for field in поля {
+                    if !known.contains(&field.name.as_str()) {
+                        let mut diagnostic = Diagnostic::error(
+                            Stage::Semantic,
+                            codes::STRUCT_FIELDS,
+                            format!("У '{}' нет поля '{}'", имя, field.name),
+                        )
+                        .with_span(field.span);
+                        diagnostic.notes.extend(
+                            suggest::closest(&field.name, known.iter().copied()).map(suggest::did_you_mean),
+                        );
+                        diagnostic.notes.push(format!("поля {}: {}", имя, known.join(", ")));
+                        problems.push(diagnostic);
+                    }
+                }
+                self.diagnostics.extend(problems);
+            }
+            _ => {}
+        }
+    }
+
+    fn check_fields<'p>(&mut self, fields: impl Iterator<Item = &'p Param>) {
+        let mut seen = HashSet::new();
+        for field in fields {
+            if !seen.insert(field.name.as_str()) {
+                self.error(
+                    codes::STRUCT_FIELDS,
+                    format!("Поле '{}' объявлено дважды", field.name),
+                    field.span,
+                );
+            }
+        }
+    }
+
+    // Родитель — класс, объявленный раньше наследника, и наследование не замыкается в круг
+    fn check_parent(&mut self, class: &str, parent: &str, span: Span, declaration: Span) {
+        let Some(info) = self.classes.get(parent) else {
+            let message = if self.structs.contains_key(parent) {
+                format!("Наследовать можно только от класса, а '{}' — структура", parent)
+            } else {
+                format!("Класс '{}' не объявлен", parent)
+            };
+            let hint = suggest::closest(parent, self.classes.keys().map(String::as_str))
+                .filter(|candidate| *candidate != class)
+                .map(suggest::did_you_mean);
+            self.error(codes::INHERITANCE, message, span).notes.extend(hint);
+            return;
+        };
+        let parent_span = info.span;
+
+        let chain = self.ancestry(parent);
+        if chain.contains(&class) {
+            let mut cycle: Vec<&str> = chain.into_iter().take_while(|name| *name != class).collect();
+            cycle.insert(0, class);
+            cycle.push(class);
+            let message = format!("Наследование замыкается в круг: {}", cycle.join(" → "));
+            self.error(codes::INHERITANCE, message, span);
+            return;
+        }
+
+        // Класс из подключённого файла уже объявлен
+        if parent_span.file == declaration.file && parent_span.start > declaration.start {
+            self.error(
+                codes::INHERITANCE,
+                format!("Класс '{}' объявлен ниже, чем наследующий от него '{}'", parent, class),
+                span,
+            )
+            .labels
+            .push(Label { span: parent_span, message: "класс объявлен здесь".to_string() });
+        }
+    }
+
+    // Тело функции; `scope` — её параметры
+    fn check_function_body(&mut self, scope: HashSet<String>, body: &[Stmt]) {
+        let was_in_function = std::mem::replace(&mut self.in_function, true);
+        // Из функции нельзя прервать цикл, внутри которого она объявлена
+        let outer_loops = std::mem::take(&mut self.loop_depth);
+        self.check_scoped_block(body, scope);
+        self.loop_depth = outer_loops;
+        self.in_function = was_in_function;
+    }
+
+    // Переменная цикла `для` видна только в его теле
+    fn check_loop_body(&mut self, variable: &str, body: &[Stmt]) {
+        self.loop_depth += 1;
+        self.check_scoped_block(body, HashSet::from([variable.to_string()]));
+        self.loop_depth -= 1;
+    }
+
+    // Присваивание меняет уже объявленную переменную и не создаёт новую.
+    // `statement` — присваивание стоит отдельной инструкцией, и перед ним можно дописать `пусть`.
+    fn check_assignment(&mut self, name: &str, value: &Expr, span: Span, statement: bool) {
+        self.check_expression(value);
+        if self.is_declared(name) {
+            return;
+        }
+        let mut diagnostic = Diagnostic::error(
+            Stage::Semantic,
+            codes::UNDECLARED_VARIABLE,
+            format!("Присваивание необъявленной переменной '{}'", name),
+        )
+        .with_span(span);
+        diagnostic.notes.extend(suggest::variable_hint(name, self.variable_names(), self.function_names()));
+        diagnostic = if statement {
+            diagnostic.with_fix(format!("Объявить переменную '{}'", name), span.start_point(), "пусть ")
+        } else {
+            diagnostic.with_note(format!("сначала объявите её: пусть {} = ...;", name))
+        };
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn check_expression(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Число(_) | ExprKind::Строка(_) | ExprKind::Булево(_) | ExprKind::Ошибка => {}
+            ExprKind::Шаблон(parts) => {
+                for part in parts {
+                    if let TemplatePart::Выражение(expr) = part {
+                        self.check_expression(expr);
+                    }
+                }
+            }
+            ExprKind::Этот => {
+                if !self.in_method {
+                    self.error(
+                        codes::THIS_OUTSIDE_METHOD,
+                        "'этот' можно использовать только внутри метода класса".to_string(),
+                        expr.span,
+                    );
+                }
+            }
+            // Объявленную функцию можно передать как значение, а встроенную — нет
+            ExprKind::Идентификатор(name) if self.is_declared(name) => {}
+            ExprKind::Идентификатор(name) if BUILTINS.contains(&name.as_str()) =>
+            {
+                self.error(
+                    codes::BUILTIN_AS_VALUE,
+                    format!("Встроенную функцию '{}' нельзя использовать как значение", name),
+                    expr.span,
+                )
+                .notes
+                .push(format!("оберните её в анонимную функцию: фн(x) {{ вернуть {}(x); }}", name));
+            }
+            ExprKind::Идентификатор(name) => {
+                let hint = suggest::variable_hint(name, self.variable_names(), self.function_names());
+                let diagnostic = self.error(
+                    codes::UNDECLARED_VARIABLE,
+                    format!("Переменная '{}' не объявлена", name),
+                    expr.span,
+                );
+                diagnostic.notes.extend(hint);
+            }
+            ExprKind::БинарнаяОперация { левый, правый, .. } => {
+                self.check_expression(левый);
+                self.check_expression(правый);
+            }
+            ExprKind::ВызовФункции { имя, аргументы } => {
+                self.check_call(имя, аргументы, expr.span);
+            }
+            ExprKind::Вызов { функция, аргументы } => {
+                self.check_expression(функция);
+                for arg in аргументы {
+                    self.check_expression(arg);
+                }
+            }
+            // Есть ли у объекта такой метод, выясняется при выполнении
+            ExprKind::ВызовМетода { объект, аргументы, .. } => {
+                self.check_expression(объект);
+                for arg in аргументы {
+                    self.check_expression(arg);
+                }
+            }
+            // Анонимная функция видит переменные, объявленные до неё
+            ExprKind::Лямбда(lambda) => {
+                let scope = lambda.params.iter().map(|param| param.name.clone()).collect();
+                self.check_function_body(scope, &lambda.body);
+            }
+            ExprKind::Отрицание(операнд) => self.check_expression(операнд),
+            ExprKind::Список(элементы) => {
+                for item in элементы {
+                    self.check_expression(item);
+                }
+            }
+            ExprKind::Словарь(пары) => {
+                for (key, value) in пары {
+                    self.check_expression(key);
+                    self.check_expression(value);
+                }
+            }
+            ExprKind::Индекс { объект, индекс } => {
+                self.check_expression(объект);
+                self.check_expression(индекс);
+            }
+            ExprKind::ПрисваиваниеПоИндексу { объект, индекс, значение } => {
+                self.check_expression(объект);
+                self.check_expression(индекс);
+                self.check_expression(значение);
+            }
+            // Тип объекта до запуска неизвестен, поэтому имя поля проверяется при выполнении
+            ExprKind::Поле { объект, .. } => self.check_expression(объект),
+            ExprKind::ПрисваиваниеПолю { объект, значение, .. } => {
+                self.check_expression(объект);
+                self.check_expression(значение);
+            }
+            ExprKind::СозданиеСтруктуры { имя, поля } => {
+                for field in поля {
+                    self.check_expression(&field.value);
+                }
+                self.check_struct_literal(имя, поля, expr.span);
+            }
+            ExprKind::Присваивание { имя, значение } => {
+                self.check_assignment(имя, значение, expr.span, false);
+            }
+        }
+    }
+
+    fn check_call(&mut self, name: &str, args: &[Expr], span: Span) {
+        for arg in args {
+            self.check_expression(arg);
+        }
+        if let Some(target) = args.first().filter(|_| name == "график") {
+            self.check_plotted_function(target);
+        }
+
+        // Параметр, переменная или функция из области видимости заслоняет
+        // одноимённую встроенную функцию, как и при выполнении
+        match self.lookup(name) {
+            // Функция, сохранённая в переменной: число параметров известно только при запуске
+            Some(Binding::Variable) => return,
+            Some(Binding::Function(info)) => {
+                if info.params != args.len() {
+                    self.error(
+                        codes::WRONG_ARGUMENT_COUNT,
+                        format!(
+                            "Функция '{}' ожидает {}, передано {}",
+                            name, arguments(info.params), args.len()
+                        ),
+                        span,
+                    )
+                    .labels
+                    .push(declaration_label(info.span));
+                }
+                return;
+            }
+            None => {}
+        }
+
+        if let Some((min, max)) = builtin_arity(name) {
+            let ok = args.len() >= min && max.is_none_or(|max| args.len() <= max);
+            if !ok {
+                let expected = match max {
+                    Some(max) if max == min => arguments(min),
+                    Some(max) => format!("от {} до {}", min, arguments(max)),
+                    None => format!("не меньше {}", arguments(min)),
+                };
+                self.error(
+                    codes::WRONG_ARGUMENT_COUNT,
+                    format!(
+                        "Функция '{}' ожидает {}, передано {}",
+                        name, expected, args.len()
+                    ),
+                    span,
+                );
+            }
+            return;
+        }
+
+        if self.classes.contains_key(name) {
+            let constructor = self.constructor(name);
+            let params = constructor.map_or(0, |(params, _)| params);
+            if params != args.len() {
+                let label = match constructor {
+                    Some((_, declared)) => Label { span: declared, message: "конструктор объявлен здесь".to_string() },
+                    None => Label { span: self.classes[name].span, message: "класс объявлен здесь, конструктора у него нет".to_string() },
+                };
+                self.error(
+                    codes::WRONG_ARGUMENT_COUNT,
+                    format!(
+                        "Конструктор класса '{}' ожидает {}, передано {}",
+                        name, arguments(params), args.len()
+                    ),
+                    span,
+                )
+                .labels
+                .push(label);
+            }
+        } else if self.structs.contains_key(name) {
+            // Конструктор структуры принимает значения всех полей по порядку
+            let info = &self.structs[name];
+            if info.fields.len() != args.len() {
+                let (fields, declared) = (info.fields.len(), info.span);
+                self.error(
+                    codes::WRONG_ARGUMENT_COUNT,
+                    format!(
+                        "Структура '{}' ожидает {} (по одному на поле), передано {}",
+                        name, arguments(fields), args.len()
+                    ),
+                    span,
+                )
+                .labels
+                .extend(declared.map(|span| Label { span, message: "структура объявлена здесь".to_string() }));
+            }
+        } else {
+            let hint = suggest::function_hint(name, self.function_names());
+            let diagnostic = self.error(
+                codes::UNDECLARED_FUNCTION,
+                format!("Функция '{}' не объявлена", name),
+                span,
+            );
+            diagnostic.notes.extend(hint);
+        }
+    }
+
+    // `Точка { x: 1, y: 2 }`: каждое поле из объявления задано ровно один раз
+    fn check_struct_literal(&mut self, name: &str, fields: &[FieldValue], span: Span) {
+        if self.classes.contains_key(name) {
+            self.error(
+                codes::UNDECLARED_STRUCT,
+                format!("'{}' — класс, а не структура", name),
+                span,
+            )
+            .notes
+            .push(format!("объект класса создаётся вызовом конструктора: {}(...)", name));
+            return;
+        }
+        let Some(info) = self.structs.get(name) else {
+            let hint = suggest::closest(name, self.structs.keys().map(String::as_str))
+                .map(suggest::did_you_mean);
+            let diagnostic = self.error(
+                codes::UNDECLARED_STRUCT,
+                format!("Структура '{}' не объявлена", name),
+                span,
+            );
+            diagnostic.notes.extend(hint);
+            return;
+        };
+        let declared = info.span.map(|span| Label { span, message: "структура объявлена здесь".to_string() });
+
+        let mut problems = Vec::new();
+        let mut seen = HashSet::new();
+        for field in fields {
+            if !info.fields.contains(&field.name) {
+                let mut diagnostic = Diagnostic::error(
+                    Stage::Semantic,
+                    codes::STRUCT_FIELDS,
+                    format!("У структуры '{}' нет поля '{}'", name, field.name),
+                )
+                .with_span(field.span);
+                diagnostic.notes.extend(
+                    suggest::closest(&field.name, info.fields.iter().map(String::as_str))
+                        .map(suggest::did_you_mean),
+                );
+                diagnostic.notes.push(format!("поля структуры {}: {}", name, info.fields.join(", ")));
+                diagnostic.labels.extend(declared.clone());
+                problems.push(diagnostic);
+            } else if !seen.insert(field.name.as_str()) {
+                problems.push(
+                    Diagnostic::error(
+                        Stage::Semantic,
+                        codes::STRUCT_FIELDS,
+                        format!("Поле '{}' задано дважды", field.name),
+                    )
+                    .with_span(field.span),
+                );
+            }
+        }
+
+        let missing: Vec<&str> = info
+            .fields
+            .iter()
+            .map(String::as_str)
+            .filter(|field| !seen.contains(field))
+            .collect();
+        if !missing.is_empty() {
+            let mut diagnostic = Diagnostic::error(
+                Stage::Semantic,
+                codes::STRUCT_FIELDS,
+                format!("Не заданы поля структуры '{}': {}", name, missing.join(", ")),
+            )
+            .with_span(span);
+            diagnostic.labels.extend(declared);
+            problems.push(diagnostic);
+        }
+        self.diagnostics.extend(problems);
+    }
+
+    // Число параметров функции для графика, если оно известно без запуска
+    fn check_plotted_function(&mut self, target: &Expr) {
+        let (params, declared) = match &target.kind {
+            ExprKind::Идентификатор(name) => match self.lookup(name) {
+                Some(Binding::Function(info)) => (info.params, Some(info.span)),
+                _ => return,
+            },
+            ExprKind::Лямбда(lambda) => (lambda.params.len(), None),
+            _ => return,
+        };
+        if params != 1 {
+            let diagnostic = self.error(
+                codes::WRONG_ARGUMENT_COUNT,
+                format!("Функция для графика должна иметь 1 параметр, а у этой их {}", params),
+                target.span,
+            );
+            diagnostic.labels.extend(declared.map(declaration_label));
+        }
+    }
+}
+
+// «1 аргумент», «2 аргумента», «5 аргументов»
+fn arguments(n: usize) -> String {
+    let word = match (n % 10, n % 100) {
+        (_, 11..=14) => "аргументов",
+        (1, _) => "аргумент",
+        (2..=4, _) => "аргумента",
+        _ => "аргументов",
+    };
+    format!("{} {}", n, word)
+}
+
+fn declaration_label(span: Span) -> Label {
+    Label {
+        span,
+        message: "функция объявлена здесь".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+    use crate::compiler::parser::Parser;
+
+    fn diagnostics(code: &str) -> Vec<Diagnostic> {
+        let (tokens, _) = Lexer::new(code.to_string()).tokenize();
+        let (program, parse_errors) = Parser::new(tokens).parse();
+        assert!(parse_errors.is_empty(), "синтаксические ошибки: {:?}", parse_errors);
+        check(&program)
+    }
+
+    fn errors(code: &str) -> Vec<String> {
+        diagnostics(code).into_iter().map(|diagnostic| diagnostic.code).collect()
+    }
+
+    #[test]
+    fn errors_are_found_before_running() {
+        assert_eq!(
+            errors("функция f(a) { вернуть a; }\nf(1, 2);\nпрервать;"),
+            [codes::WRONG_ARGUMENT_COUNT, codes::JUMP_OUTSIDE_LOOP]
+        );
+        assert_eq!(errors("вернуть 1;"), [codes::RETURN_OUTSIDE_FUNCTION]);
+        assert_eq!(errors("печать(этот);"), [codes::THIS_OUTSIDE_METHOD]);
+        assert_eq!(errors("неизвестная();"), [codes::UNDECLARED_FUNCTION]);
+    }
+
+    #[test]
+    fn unknown_name_suggests_the_closest_one() {
+        let found = diagnostics("пусть счётчик = 1; печать(счетчик);");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, codes::UNDECLARED_VARIABLE);
+        assert_eq!(found[0].notes, ["возможно, имелось в виду 'счётчик'"]);
+    }
+
+    #[test]
+    fn code_after_return_is_unreachable() {
+        let found = diagnostics("функция f() { вернуть 1; печать(2); }\nf();");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code, codes::UNREACHABLE_CODE);
+        assert!(!found[0].is_error());
+    }
+    
+    #[test]
+    fn functions_are_visible_in_their_block_only() {
+        // Функцию блока можно вызвать выше объявления
+        assert!(errors("f();\nфункция f() { вернуть 1; }").is_empty());
+        assert!(errors("если (истина) { f(); функция f() { вернуть 1; } }").is_empty());
+        // Но не из другого блока и не снаружи функции, где она объявлена
+        assert_eq!(
+            errors("если (ложь) { функция h() { вернуть 1; } }\nпечать(h());"),
+            [codes::UNDECLARED_FUNCTION]
+        );
+        assert_eq!(
+            errors("функция внеш() { функция внутр() { вернуть 1; } вернуть внутр(); }\nвнеш();\nвнутр();"),
+            [codes::UNDECLARED_FUNCTION]
+        );
+    }
+    
+    #[test]
+    fn parameter_shadows_functions_of_the_same_name() {
+        assert!(errors("функция g(длина) { вернуть длина(1, 2); }\ng(фн(a, b) { вернуть a + b; });").is_empty());
+        let code = "функция f(a, b) { вернуть a; }
+                    функция применить(f) { вернуть f(1); }
+                    применить(фн(x) { вернуть x; });";
+        assert!(errors(code).is_empty());
+    }
+}
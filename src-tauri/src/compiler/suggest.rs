@@ -0,0 +1,167 @@
+//! Подбор похожих имён для подсказок «возможно, имелось в виду ...».
+
+use crate::compiler::lexer::KEYWORDS;
+
+// Слово в виде для сравнения: нижний регистр, «ё» и «е» не различаются
+fn normalize(word: &str) -> Vec<char> {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .map(|ch| if ch == 'ё' { 'е' } else { ch })
+        .collect()
+}
+
+/// Расстояние редактирования (вставка, удаление, замена и перестановка
+/// соседних букв), в котором «ё» и «е» считаются одной буквой.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = normalize(a);
+    let b = normalize(b);
+
+    // d[i][j] — расстояние между a[..i] и b[..j]
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+/// Ближайшее к `name` имя из `candidates` или `None`, если ничего
+/// достаточно похожего нет. Само `name` подсказкой не считается.
+pub fn closest<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let len = name.chars().count();
+    // В коротких именах одна опечатка превращает любое имя в любое другое
+    let max_distance = if len < 3 { 0 } else { (len / 3).max(1) };
+    let normalized = normalize(name);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .filter_map(|candidate| {
+            let distance = edit_distance(name, candidate);
+            // «синус» → «син»: сокращённое имя встроенной функции
+            let prefix = len >= 3
+                && candidate.chars().count() >= 3
+                && (normalized.starts_with(&normalize(candidate))
+                    || normalize(candidate).starts_with(&normalized));
+            if distance <= max_distance || prefix {
+                Some((distance, candidate))
+            } else {
+                None
+            }
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// До `limit` имён из `candidates`, похожих на `name`, от самого похожего.
+pub fn similar<'a, I>(name: &str, candidates: I, limit: usize) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut found: Vec<&str> = Vec::new();
+    let mut rest: Vec<&str> = candidates.into_iter().collect();
+    while found.len() < limit {
+        let Some(best) = closest(name, rest.iter().copied()) else {
+            break;
+        };
+        found.push(best);
+        rest.retain(|candidate| *candidate != best);
+    }
+    found
+}
+
+/// Текст подсказки для найденного похожего имени.
+pub fn did_you_mean(candidate: &str) -> String {
+    format!("возможно, имелось в виду '{}'", candidate)
+}
+
+fn keyword_hint(name: &str) -> Option<String> {
+    suggest_keyword(name).map(|word| format!("возможно, имелось в виду ключевое слово '{}'", word))
+}
+
+/// Ключевое слово, на которое похоже `name`.
+pub fn suggest_keyword(name: &str) -> Option<&'static str> {
+    closest(name, KEYWORDS.iter().map(|(word, _)| *word))
+}
+
+/// Подсказка для неизвестной переменной: похожая переменная,
+/// ключевое слово или, в крайнем случае, функция.
+pub fn variable_hint<'a>(
+    name: &str,
+    variables: impl IntoIterator<Item = &'a str>,
+    functions: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    closest(name, variables)
+        .map(did_you_mean)
+        .or_else(|| keyword_hint(name))
+        .or_else(|| {
+            closest(name, functions)
+                .map(|function| format!("есть функция с похожим именем '{}'", function))
+        })
+}
+
+/// Подсказка для неизвестной функции: похожая функция или ключевое слово.
+pub fn function_hint<'a>(
+    name: &str,
+    functions: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    closest(name, functions)
+        .map(did_you_mean)
+        .or_else(|| keyword_hint(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_ignores_case_and_ё() {
+        assert_eq!(edit_distance("Счётчик", "счетчик"), 0);
+        assert_eq!(edit_distance("пусть", "пустьь"), 1);
+        // Перестановка соседних букв — одна правка
+        assert_eq!(edit_distance("пусьт", "пусть"), 1);
+    }
+
+    #[test]
+    fn closest_name_is_suggested() {
+        assert_eq!(closest("счетчик", ["сумма", "счётчик"]), Some("счётчик"));
+        assert_eq!(closest("синус", ["син", "кос"]), Some("син"));
+        // В коротких именах опечатку не угадать
+        assert_eq!(closest("аб", ["ав"]), None);
+        assert_eq!(closest("сумма", ["сумма"]), None);
+    }
+
+    #[test]
+    fn variable_is_preferred_over_keyword_and_function() {
+        assert_eq!(
+            variable_hint("счетчик", ["счётчик"], ["счётчики"]).as_deref(),
+            Some("возможно, имелось в виду 'счётчик'")
+        );
+        assert_eq!(
+            variable_hint("пуст", [], []).as_deref(),
+            Some("возможно, имелось в виду ключевое слово 'пусть'")
+        );
+        assert_eq!(
+            variable_hint("печатъ", [], ["печать"]).as_deref(),
+            Some("есть функция с похожим именем 'печать'")
+        );
+    }
+}
@@ -0,0 +1,1321 @@
+//! Проверка типов до запуска. Типы берутся из аннотаций, литералов и
+//! встроенных функций и выводятся дальше по программе. Где тип неизвестен,
+//! проверка молчит, так что код без аннотаций остаётся динамическим.
+
+use crate::compiler::ast::*;
+use crate::compiler::diagnostics::{codes, Diagnostic, Label, Stage};
+use crate::compiler::interpreter::CONSTRUCTOR;
+use crate::compiler::lexer::Span;
+use crate::compiler::modules::Module;
+use crate::compiler::semantic::always_returns;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// Тип значения, выведенный без запуска; `None` — тип неизвестен
+type Inferred = Option<Type>;
+
+// Общий тип двух путей выполнения
+fn join(a: Inferred, b: Inferred) -> Inferred {
+    if a == b {
+        a
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Variable {
+    ty: Inferred,
+    // Тип из аннотации: переменной нельзя присвоить значение другого типа
+    annotation: Option<(Type, Span)>,
+}
+
+type Scope = HashMap<String, Variable>;
+
+fn join_scopes(a: &Scope, b: &Scope) -> Scope {
+    let mut joined = a.clone();
+    for (name, var) in b {
+        joined
+            .entry(name.clone())
+            .and_modify(|existing| existing.ty = join(existing.ty, var.ty))
+            .or_insert_with(|| var.clone());
+    }
+    joined
+}
+
+// Типы параметров встроенной функции (пустой список — любой тип) и тип результата
+fn builtin_signature(name: &str) -> Option<(&'static [&'static [Type]], Inferred)> {
+    const NUMBER: &[Type] = &[Type::Число];
+    const LIST: &[Type] = &[Type::Список];
+    const DICT: &[Type] = &[Type::Словарь];
+    const KEY: &[Type] = &[Type::Число, Type::Строка];
+    const ANY: &[Type] = &[];
+    match name {
+        "син" | "кос" | "эксп" | "лог" | "корень" => Some((&[NUMBER], Some(Type::Число))),
+        "длина" => Some((&[&[Type::Строка, Type::Список, Type::Словарь]], Some(Type::Число))),
+        "добавить" => Some((&[LIST, ANY], Some(Type::Пусто))),
+        "удалить" => Some((&[&[Type::Список, Type::Словарь], KEY], None)),
+        "ключи" | "значения" => Some((&[DICT], Some(Type::Список))),
+        "содержит" => Some((&[DICT, KEY], Some(Type::Булево))),
+        "вставить" => Some((&[LIST, NUMBER, ANY], Some(Type::Пусто))),
+        _ => None,
+    }
+}
+
+// Узел программы, который обходят `visit_block` и `visit_expression`
+enum Node<'a> {
+    Expression(&'a Expr),
+    // Тело именованной функции, лямбды или метода с параметрами
+    Function(&'a [Param], &'a [Stmt]),
+}
+
+// Обходит все выражения и тела функций в блоке, включая вложенные
+fn visit_block<'a>(stmts: &'a [Stmt], visit: &mut dyn FnMut(Node<'a>)) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StmtKind::ОбъявлениеПеременной { значение: expr, .. }
+            | StmtKind::Бросить(expr)
+            | StmtKind::Вернуть(expr)
+            | StmtKind::Выражение(expr) => visit_expression(expr, visit),
+            StmtKind::Если { условие, тогда, иначе } => {
+                visit_expression(условие, visit);
+                visit_block(тогда, visit);
+                if let Some(иначе) = иначе {
+                    visit_block(иначе, visit);
+                }
+            }
+            StmtKind::Выбор { значение, случаи, иначе } => {
+                visit_expression(значение, visit);
+                for case in случаи {
+                    visit_block(&case.body, visit);
+                }
+                if let Some(иначе) = иначе {
+                    visit_block(иначе, visit);
+                }
+            }
+            StmtKind::Попытка { тело, перехват, наконец } => {
+                visit_block(тело, visit);
+                if let Some(catch) = перехват {
+                    visit_block(&catch.body, visit);
+                }
+                if let Some(наконец) = наконец {
+                    visit_block(наконец, visit);
+                }
+            }
+            StmtKind::Пока { условие, тело } => {
+                visit_expression(условие, visit);
+                visit_block(тело, visit);
+            }
+            StmtKind::Для { начало, конец, шаг, тело, .. } => {
+                for bound in [Some(начало), Some(конец), шаг.as_ref()].into_iter().flatten() {
+                    visit_expression(bound, visit);
+                }
+                visit_block(тело, visit);
+            }
+            StmtKind::ДляКаждого { коллекция, тело, .. } => {
+                visit_expression(коллекция, visit);
+                visit_block(тело, visit);
+            }
+            StmtKind::ОбъявлениеФункции { параметры, тело, .. } => {
+                visit(Node::Function(параметры, тело));
+                visit_block(тело, visit);
+            }
+            StmtKind::ОбъявлениеКласса { поля, методы, .. } => {
+                for value in поля.iter().filter_map(|field| field.value.as_ref()) {
+                    visit_expression(value, visit);
+                }
+                for method in методы {
+                    visit(Node::Function(&method.function.params, &method.function.body));
+                    visit_block(&method.function.body, visit);
+                }
+            }
+            StmtKind::Прервать | StmtKind::Продолжить | StmtKind::ОбъявлениеСтруктуры { .. } => {}
+        }
+    }
+}
+
+fn visit_expression<'a>(expr: &'a Expr, visit: &mut dyn FnMut(Node<'a>)) {
+    visit(Node::Expression(expr));
+    match &expr.kind {
+        ExprKind::Число(_)
+        | ExprKind::Строка(_)
+        | ExprKind::Булево(_)
+        | ExprKind::Идентификатор(_)
+        | ExprKind::Этот
+        | ExprKind::Ошибка => {}
+        ExprKind::Шаблон(parts) => {
+            for part in parts {
+                if let TemplatePart::Выражение(expr) = part {
+                    visit_expression(expr, visit);
+                }
+            }
+        }
+        ExprKind::БинарнаяОперация { левый, правый, .. } => {
+            visit_expression(левый, visit);
+            visit_expression(правый, visit);
+        }
+        ExprKind::ВызовФункции { аргументы, .. } => {
+            for arg in аргументы {
+                visit_expression(arg, visit);
+            }
+        }
+        ExprKind::Вызов { функция: object, аргументы }
+        | ExprKind::ВызовМетода { объект: object, аргументы, .. } => {
+            visit_expression(object, visit);
+            for arg in аргументы {
+                visit_expression(arg, visit);
+            }
+        }
+        ExprKind::Лямбда(lambda) => {
+            visit(Node::Function(&lambda.params, &lambda.body));
+            visit_block(&lambda.body, visit);
+        }
+        ExprKind::Присваивание { значение: operand, .. }
+        | ExprKind::Поле { объект: operand, .. }
+        | ExprKind::Отрицание(operand) => visit_expression(operand, visit),
+        ExprKind::Список(элементы) => {
+            for item in элементы {
+                visit_expression(item, visit);
+            }
+        }
+        ExprKind::Словарь(пары) => {
+            for (key, value) in пары {
+                visit_expression(key, visit);
+                visit_expression(value, visit);
+            }
+        }
+        ExprKind::Индекс { объект, индекс } => {
+            visit_expression(объект, visit);
+            visit_expression(индекс, visit);
+        }
+        ExprKind::ПрисваиваниеПоИндексу { объект, индекс, значение } => {
+            visit_expression(объект, visit);
+            visit_expression(индекс, visit);
+            visit_expression(значение, visit);
+        }
+        ExprKind::ПрисваиваниеПолю { объект, значение, .. } => {
+            visit_expression(объект, visit);
+            visit_expression(значение, visit);
+        }
+        ExprKind::СозданиеСтруктуры { поля, .. } => {
+            for field in поля {
+                visit_expression(&field.value, visit);
+            }
+        }
+    }
+}
+
+// Внешние переменные, которым присваивают значения тела функций, лямбд и
+// методов. Параметры и объявления на верхнем уровне тела — свои переменные
+// функции; объявления во вложенных блоках не учитываются, так что имя
+// может попасть сюда лишний раз, но не потеряется.
+fn reassigned_by_functions(program: &Program) -> HashSet<String> {
+    let mut reassigned = HashSet::new();
+    visit_block(&program.statements, &mut |node| {
+        let Node::Function(params, body) = node else {
+            return;
+        };
+        let local: HashSet<&str> = params
+            .iter()
+            .map(|param| param.name.as_str())
+            .chain(body.iter().filter_map(|stmt| match &stmt.kind {
+                StmtKind::ОбъявлениеПеременной { имя, .. } | StmtKind::ОбъявлениеФункции { имя, .. } => {
+                    Some(имя.as_str())
+                }
+                _ => None,
+            }))
+            .collect();
+        visit_block(body, &mut |node| {
+            if let Node::Expression(Expr { kind: ExprKind::Присваивание { имя, .. }, .. }) = node {
+                if !local.contains(имя.as_str()) {
+                    reassigned.insert(имя.clone());
+                }
+            }
+        });
+    });
+    reassigned
+}
+
+fn one_of(types: &[Type]) -> String {
+    types.iter().map(|ty| ty.name()).collect::<Vec<_>>().join(" или ")
+}
+
+#[derive(Clone, Copy)]
+struct Function<'a> {
+    params: &'a [Param],
+    result: Option<&'a TypeAnnotation>,
+    body: &'a [Stmt],
+    span: Span,
+}
+
+// Одноимённые функции бывают в разных блоках, поэтому функцию отличает место объявления
+type FunctionKey = (u32, u32);
+
+fn function_key(span: Span) -> FunctionKey {
+    (span.file, span.start)
+}
+
+#[derive(Clone, Copy)]
+struct Class<'a> {
+    parent: Option<&'a str>,
+    methods: &'a [Method],
+}
+
+struct Checker<'a> {
+    // Функции, видимые в текущем блоке
+    functions: HashMap<String, Function<'a>>,
+    // Поля структур: по ним проверяются значения при создании
+    structs: HashMap<String, &'a [Param]>,
+    classes: HashMap<String, Class<'a>>,
+    // Выведенные типы результатов функций без аннотации
+    inferred_results: HashMap<FunctionKey, Inferred>,
+    inferring: HashSet<FunctionKey>,
+    variables: Scope,
+    // Переменные, которые может изменить вызов функции: после вызова их
+    // выведенный тип неизвестен
+    reassigned: HashSet<String>,
+    // Аннотация результата функции, тело которой проверяется сейчас
+    expected_result: Option<&'a TypeAnnotation>,
+    // Типы значений во всех `вернуть` текущей функции
+    returns: Vec<Inferred>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// Объявления верхнего уровня модуля, которые можно подключить
+#[derive(Default)]
+struct Exports<'a> {
+    functions: HashMap<String, Function<'a>>,
+    variables: Scope,
+    structs: HashMap<String, &'a [Param]>,
+    classes: HashMap<String, Class<'a>>,
+}
+
+/// Проверяет согласованность типов в программе, не выполняя её.
+pub fn check(program: &Program) -> Vec<Diagnostic> {
+    let mut checker = Checker::new(program);
+    checker.check_program(program);
+    checker.diagnostics
+}
+
+/// Проверяет типы в программе из нескольких файлов: каждый модуль видит свои
+/// объявления и подключённые имена.
+pub fn check_modules(modules: &[Module]) -> Vec<Diagnostic> {
+    let mut exports: Vec<Exports> = Vec::with_capacity(modules.len());
+    // Результаты подключённых функций выводятся в их собственном модуле
+    let mut inferred_results = HashMap::new();
+    let mut diagnostics = Vec::new();
+    for module in modules {
+        let mut checker = Checker::new(&module.program);
+        checker.inferred_results = inferred_results;
+        for (index, name) in &module.imports {
+            let from = &exports[*index];
+            if let Some(function) = from.functions.get(name) {
+                checker.functions.insert(name.clone(), *function);
+            }
+            if let Some(var) = from.variables.get(name) {
+                checker.variables.insert(name.clone(), var.clone());
+            }
+            if let Some(fields) = from.structs.get(name) {
+                checker.structs.insert(name.clone(), fields);
+            }
+            if let Some(class) = from.classes.get(name) {
+                checker.classes.insert(name.clone(), *class);
+            }
+        }
+        checker.check_program(&module.program);
+        exports.push(checker.exports(&module.program));
+        inferred_results = checker.inferred_results;
+        diagnostics.extend(checker.diagnostics);
+    }
+    diagnostics
+}
+
+fn param_scope(params: &[Param]) -> Scope {
+    params
+        .iter()
+        .map(|param| {
+            let var = Variable {
+                ty: param.ty.as_ref().map(|ann| ann.ty),
+                annotation: param.ty.as_ref().map(|ann| (ann.ty, ann.span)),
+            };
+            (param.name.clone(), var)
+        })
+        .collect()
+}
+
+impl<'a> Checker<'a> {
+    fn new(program: &Program) -> Self {
+        Checker {
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            classes: HashMap::new(),
+            inferred_results: HashMap::new(),
+            inferring: HashSet::new(),
+            variables: Scope::new(),
+            reassigned: reassigned_by_functions(program),
+            expected_result: None,
+            returns: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn check_program(&mut self, program: &'a Program) {
+        self.collect_types(&program.statements);
+        self.check_block(&program.statements);
+    }
+
+    // Объявления верхнего уровня проверенного модуля. Переменную подключивший
+    // видит позже, когда её тип мог смениться, поэтому известен только тип из
+    // аннотации. У класса остаётся только конструктор: родителя подключивший
+    // может не видеть.
+    fn exports(&mut self, program: &'a Program) -> Exports<'a> {
+        let mut exports = Exports::default();
+        for stmt in &program.statements {
+            match &stmt.kind {
+                StmtKind::ОбъявлениеПеременной { имя, .. } => {
+                    if let Some(var) = self.variables.get(имя) {
+                        let ty = var.annotation.map(|(ty, _)| ty);
+                        exports.variables.insert(имя.clone(), Variable { ty, annotation: var.annotation });
+                    }
+                }
+                StmtKind::ОбъявлениеФункции { имя, параметры, тип_результата, тело } => {
+                    if тип_результата.is_none() {
+                        self.infer_result(имя, function_key(stmt.span));
+                    }
+                    let function =
+                        Function { params: параметры, result: тип_результата.as_ref(), body: тело, span: stmt.span };
+                    exports.functions.insert(имя.clone(), function);
+                }
+                StmtKind::ОбъявлениеСтруктуры { имя, поля } => {
+                    exports.structs.insert(имя.clone(), поля);
+                }
+                StmtKind::ОбъявлениеКласса { имя, .. } => {
+                    let methods = self.constructor(имя).map_or(&[][..], std::slice::from_ref);
+                    exports.classes.insert(имя.clone(), Class { parent: None, methods });
+                }
+                _ => {}
+            }
+        }
+        exports
+    }
+
+    // Структуры и классы видны из любого места программы, поэтому собираем их заранее
+    fn collect_types(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::ОбъявлениеФункции { тело, .. } => self.collect_types(тело),
+                StmtKind::ОбъявлениеСтруктуры { имя, поля } => {
+                    self.structs.insert(имя.clone(), поля);
+                }
+                StmtKind::ОбъявлениеКласса { имя, родитель, методы, .. } => {
+                    let parent = родитель.as_ref().map(|(name, _)| name.as_str());
+                    self.classes.insert(имя.clone(), Class { parent, methods: методы });
+                }
+                StmtKind::Если { тогда, иначе, .. } => {
+                    self.collect_types(тогда);
+                    if let Some(иначе) = иначе {
+                        self.collect_types(иначе);
+                    }
+                }
+                StmtKind::Выбор { случаи, иначе, .. } => {
+                    for case in случаи {
+                        self.collect_types(&case.body);
+                    }
+                    if let Some(иначе) = иначе {
+                        self.collect_types(иначе);
+                    }
+                }
+                StmtKind::Попытка { тело, перехват, наконец } => {
+                    self.collect_types(тело);
+                    if let Some(catch) = перехват {
+                        self.collect_types(&catch.body);
+                    }
+                    if let Some(наконец) = наконец {
+                        self.collect_types(наконец);
+                    }
+                }
+                StmtKind::Пока { тело, .. }
+                | StmtKind::Для { тело, .. }
+                | StmtKind::ДляКаждого { тело, .. } => self.collect_types(тело),
+                _ => {}
+            }
+        }
+    }
+
+    fn mismatch(&mut self, expected: impl fmt::Display, actual: Type, span: Span) -> &mut Diagnostic {
+        self.diagnostics.push(
+            Diagnostic::error(
+                Stage::Semantic,
+                codes::TYPE_MISMATCH,
+                format!("Несовпадение типов: ожидается {}, получено {}", expected, actual),
+            )
+            .with_span(span),
+        );
+        self.diagnostics.last_mut().unwrap()
+    }
+
+    // Значение должно иметь тип из аннотации
+    fn expect_annotated(&mut self, expected: (Type, Span), actual: Inferred, span: Span) {
+        match actual {
+            Some(actual) if actual != expected.0 => {
+                self.mismatch(expected.0, actual, span).labels.push(Label {
+                    span: expected.1,
+                    message: "тип указан здесь".to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Проверка без сообщений: нужна, чтобы узнать типы после блока
+    fn check_silently(&mut self, stmts: &'a [Stmt]) {
+        let reported = self.diagnostics.len();
+        let returns = self.returns.len();
+        self.check_scoped_block(stmts);
+        self.diagnostics.truncate(reported);
+        self.returns.truncate(returns);
+    }
+
+    // Функции блока видны во всём блоке, в том числе выше своего объявления,
+    // и заслоняют одноимённые внешние переменные
+    fn check_block(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            if let StmtKind::ОбъявлениеФункции { имя, параметры, тип_результата, тело } = &stmt.kind {
+                self.variables.remove(имя);
+                self.functions.insert(
+                    имя.clone(),
+                    Function { params: параметры, result: тип_результата.as_ref(), body: тело, span: stmt.span },
+                );
+            }
+        }
+        for stmt in stmts {
+            self.check_statement(stmt);
+        }
+    }
+
+    // Блок со своей областью видимости: объявленные в нём переменные и функции
+    // после него не видны, а затенённые ими внешние видны снова
+    fn check_scoped_block(&mut self, stmts: &'a [Stmt]) {
+        let outer = self.variables.clone();
+        let outer_functions = self.functions.clone();
+        self.check_block(stmts);
+        self.functions = outer_functions;
+        for stmt in stmts {
+            if let StmtKind::ОбъявлениеПеременной { имя, .. } | StmtKind::ОбъявлениеФункции { имя, .. } = &stmt.kind {
+                match outer.get(имя) {
+                    Some(var) => self.variables.insert(имя.clone(), var.clone()),
+                    None => self.variables.remove(имя),
+                };
+            }
+        }
+    }
+
+    // Внешние переменные, как их видит тело функции. Функция выполняется
+    // позже, и тип к тому времени может смениться, поэтому известен только
+    // тип из аннотации.
+    fn outer_scope(&self, params: &[Param]) -> Scope {
+        let mut scope: Scope = self
+            .variables
+            .iter()
+            .map(|(name, var)| {
+                let ty = var.annotation.map(|(ty, _)| ty);
+                (name.clone(), Variable { ty, annotation: var.annotation })
+            })
+            .collect();
+        scope.extend(param_scope(params));
+        scope
+    }
+
+    // Вызванная функция могла присвоить внешним переменным значения другого типа
+    fn forget_reassigned(&mut self) {
+        for name in &self.reassigned {
+            if let Some(var) = self.variables.get_mut(name).filter(|var| var.annotation.is_none()) {
+                var.ty = None;
+            }
+        }
+    }
+
+    fn check_statement(&mut self, stmt: &'a Stmt) {
+        match &stmt.kind {
+            StmtKind::ОбъявлениеПеременной { имя, тип, значение } => {
+                let actual = self.check_expression(значение);
+                let annotation = тип.as_ref().map(|ann| (ann.ty, ann.span));
+                if let Some(annotation) = annotation {
+                    self.expect_annotated(annotation, actual, значение.span);
+                }
+                let ty = annotation.map(|(ty, _)| ty).or(actual);
+                self.variables.insert(имя.clone(), Variable { ty, annotation });
+            }
+            StmtKind::Если { условие, тогда, иначе } => {
+                self.check_condition(условие);
+                let before = self.variables.clone();
+                self.check_scoped_block(тогда);
+                let after_then = std::mem::replace(&mut self.variables, before);
+                if let Some(иначе) = иначе {
+                    self.check_scoped_block(иначе);
+                }
+                self.variables = join_scopes(&after_then, &self.variables);
+            }
+            StmtKind::Выбор { значение, случаи, иначе } => {
+                let actual = self.check_expression(значение);
+                let before = self.variables.clone();
+                let mut branches = Vec::new();
+                for case in случаи {
+                    for pattern in &case.patterns {
+                        self.check_pattern(pattern, actual);
+                    }
+                    self.check_case_body(&case.patterns, actual, &case.body);
+                    branches.push(std::mem::replace(&mut self.variables, before.clone()));
+                }
+                match иначе {
+                    Some(иначе) => {
+                        self.check_scoped_block(иначе);
+                        branches.push(std::mem::replace(&mut self.variables, before.clone()));
+                    }
+                    None => {
+                        branches.push(before);
+                        self.check_exhaustive(случаи, actual, значение.span);
+                    }
+                }
+                if let Some(joined) = branches.into_iter().reduce(|a, b| join_scopes(&a, &b)) {
+                    self.variables = joined;
+                }
+            }
+            // Ошибка может прервать тело где угодно, поэтому `перехват` и `наконец`
+            // видят переменные такими, какими они были до тела или стали после него
+            StmtKind::Попытка { тело, перехват, наконец } => {
+                let before = self.variables.clone();
+                self.check_scoped_block(тело);
+                let mut after = self.variables.clone();
+                if let Some(catch) = перехват {
+                    self.variables = join_scopes(&before, &after);
+                    let outer = self.variables.get(&catch.name).cloned();
+                    let error = Variable { ty: Some(Type::Структура), annotation: None };
+                    self.variables.insert(catch.name.clone(), error);
+                    self.check_scoped_block(&catch.body);
+                    match outer {
+                        Some(var) => self.variables.insert(catch.name.clone(), var),
+                        None => self.variables.remove(&catch.name),
+                    };
+                    after = join_scopes(&after, &self.variables);
+                }
+                if let Some(наконец) = наконец {
+                    self.variables = join_scopes(&before, &after);
+                    self.check_scoped_block(наконец);
+                } else {
+                    self.variables = after;
+                }
+            }
+            StmtKind::Бросить(expr) => {
+                let actual = self.check_expression(expr);
+                self.expect_one_of(&[Type::Строка, Type::Структура], actual, expr.span);
+            }
+            StmtKind::Пока { условие, тело } => self.check_loop(Some(условие), None, тело),
+            StmtKind::Для { переменная, начало, конец, шаг, тело } => {
+                for bound in [Some(начало), Some(конец), шаг.as_ref()].into_iter().flatten() {
+                    let actual = self.check_expression(bound);
+                    self.expect_type(Type::Число, actual, bound.span);
+                }
+                self.check_loop(None, Some((переменная, Some(Type::Число))), тело);
+            }
+            StmtKind::ДляКаждого { переменная, коллекция, тело } => {
+                let actual = self.check_expression(коллекция);
+                self.expect_one_of(&[Type::Строка, Type::Список, Type::Словарь], actual, коллекция.span);
+                // Символы строки — строки, а тип элементов и ключей неизвестен
+                let item = actual.filter(|ty| *ty == Type::Строка);
+                self.check_loop(None, Some((переменная, item)), тело);
+            }
+            StmtKind::Вернуть(expr) => {
+                let actual = self.check_expression(expr);
+                if let Some(expected) = self.expected_result {
+                    self.expect_annotated((expected.ty, expected.span), actual, expr.span);
+                }
+                self.returns.push(actual);
+            }
+            StmtKind::Выражение(expr) => {
+                self.check_expression(expr);
+            }
+            StmtKind::Прервать | StmtKind::Продолжить => {}
+            StmtKind::ОбъявлениеФункции { имя, параметры, тип_результата, тело } => {
+                let scope = self.outer_scope(параметры);
+                self.check_function(Some(имя), scope, тип_результата.as_ref(), тело, stmt.span);
+            }
+            StmtKind::ОбъявлениеСтруктуры { .. } => {}
+            StmtKind::ОбъявлениеКласса { поля, методы, .. } => {
+                for field in поля {
+                    let Some(value) = &field.value else {
+                        continue;
+                    };
+                    let actual = self.check_expression(value);
+                    if let Some(annotation) = &field.param.ty {
+                        self.expect_annotated((annotation.ty, annotation.span), actual, value.span);
+                    }
+                }
+                for method in методы {
+                    let function = &method.function;
+                    let scope = self.outer_scope(&function.params);
+                    self.check_function(Some(&method.name), scope, function.result.as_ref(), &function.body, method.span);
+                }
+            }
+        }
+    }
+
+    // Параметры конструктора; у класса без конструктора их нет
+    fn constructor_params(&self, class: &str) -> &'a [Param] {
+        self.constructor(class).map_or(&[], |method| &method.function.params)
+    }
+
+    // Конструктор класса: свой или ближайшего родителя. Цикл наследования
+    // уже найден семантической проверкой, здесь он просто обрывается.
+    fn constructor(&self, class: &str) -> Option<&'a Method> {
+        let mut current = self.classes.get(class);
+        for _ in 0..self.classes.len() {
+            let info = current?;
+            if let Some(method) = info.methods.iter().find(|method| method.name == CONSTRUCTOR) {
+                return Some(method);
+            }
+            current = info.parent.and_then(|parent| self.classes.get(parent));
+        }
+        None
+    }
+
+    // Тело функции с переменными `scope`; `name` нет у анонимной функции
+    fn check_function(
+        &mut self,
+        name: Option<&str>,
+        scope: Scope,
+        result: Option<&'a TypeAnnotation>,
+        body: &'a [Stmt],
+        span: Span,
+    ) {
+        // Параметры заслоняют одноимённые функции
+        let outer_functions = self.functions.clone();
+        self.functions.retain(|name, _| !scope.contains_key(name));
+        let outer = std::mem::replace(&mut self.variables, scope);
+        let outer_result = std::mem::replace(&mut self.expected_result, result);
+        let outer_returns = std::mem::take(&mut self.returns);
+        self.check_block(body);
+        self.returns = outer_returns;
+        self.expected_result = outer_result;
+        self.variables = outer;
+        self.functions = outer_functions;
+
+        let Some(result) = result else {
+            return;
+        };
+        if result.ty != Type::Пусто && !always_returns(body) {
+            let function = match name {
+                Some(name) => format!("Функция '{}'", name),
+                None => "Анонимная функция".to_string(),
+            };
+            self.diagnostics.push(
+                Diagnostic::error(
+                    Stage::Semantic,
+                    codes::MISSING_RETURN,
+                    format!(
+                        "{} должна вернуть {}, но может завершиться без 'вернуть'",
+                        function, result.ty
+                    ),
+                )
+                .with_span(span)
+                .with_label(result.span, "тип результата указан здесь"),
+            );
+        }
+    }
+
+    // Образец, который никогда не совпадёт со значением известного типа, — ошибка
+    fn check_pattern(&mut self, pattern: &Pattern, expected: Inferred) {
+        let (actual, allowed): (Type, &[Type]) = match &pattern.kind {
+            PatternKind::Переменная(_) => return,
+            PatternKind::Число(_) | PatternKind::Диапазон(..) => (Type::Число, &[Type::Число]),
+            PatternKind::Строка(_) => (Type::Строка, &[Type::Строка]),
+            PatternKind::Булево(_) => (Type::Булево, &[Type::Булево]),
+            PatternKind::Список(items) => {
+                for item in items {
+                    self.check_pattern(item, None);
+                }
+                (Type::Список, &[Type::Список])
+            }
+            PatternKind::Структура { имя, поля } => {
+                let declared = self.structs.get(имя).copied().unwrap_or_default();
+                for field in поля {
+                    let annotation = declared
+                        .iter()
+                        .find(|param| param.name == field.name)
+                        .and_then(|param| param.ty.as_ref())
+                        .map(|ann| ann.ty);
+                    self.check_pattern(&field.pattern, annotation);
+                }
+                let ty = if self.classes.contains_key(имя) { Type::Объект } else { Type::Структура };
+                (ty, &[Type::Структура, Type::Объект])
+            }
+        };
+        if let Some(expected) = expected.filter(|ty| !allowed.contains(ty)) {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    Stage::Semantic,
+                    codes::TYPE_MISMATCH,
+                    format!("Образец типа {} не совпадёт со значением типа {}", actual, expected),
+                )
+                .with_span(pattern.span)
+                .with_note("этот образец никогда не подойдёт"),
+            );
+        }
+    }
+
+    // Тело случая: имена из образца видны только в нём. Тип известен только у
+    // имени, которое целиком заменяет значение в единственном образце.
+    fn check_case_body(&mut self, patterns: &'a [Pattern], actual: Inferred, body: &'a [Stmt]) {
+        let outer = self.variables.clone();
+        let whole = matches!(patterns, [pattern] if pattern.is_catch_all());
+        let mut names = Vec::new();
+        for pattern in patterns {
+            for (name, _) in pattern.bindings() {
+                let ty = if whole { actual } else { None };
+                self.variables.insert(name.to_string(), Variable { ty, annotation: None });
+                names.push(name);
+            }
+        }
+        self.check_scoped_block(body);
+        for name in names {
+            match outer.get(name) {
+                Some(var) => self.variables.insert(name.to_string(), var.clone()),
+                None => self.variables.remove(name),
+            };
+        }
+    }
+
+    // Выбор без `иначе` и без случая на любое значение: для булева значения
+    // видно, какого варианта не хватает, остальные типы случаями не перечислить
+    fn check_exhaustive(&mut self, cases: &[Case], actual: Inferred, span: Span) {
+        let Some(actual) = actual else {
+            return;
+        };
+        let patterns = || cases.iter().flat_map(|case| &case.patterns);
+        if patterns().any(Pattern::is_catch_all) {
+            return;
+        }
+        let message = if actual == Type::Булево {
+            let missing: Vec<&str> = [(true, "истина"), (false, "ложь")]
+                .into_iter()
+                .filter(|(value, _)| {
+                    !patterns().any(|pattern| matches!(pattern.kind, PatternKind::Булево(b) if b == *value))
+                })
+                .map(|(_, name)| name)
+                .collect();
+            if missing.is_empty() {
+                return;
+            }
+            format!("Выбор не разбирает значение {}", missing.join(" и "))
+        } else {
+            format!("Выбор разбирает не все значения типа {}", actual)
+        };
+        self.diagnostics.push(
+            Diagnostic::warning(Stage::Semantic, codes::NON_EXHAUSTIVE_MATCH, message)
+                .with_span(span)
+                .with_note("если ни один случай не подойдёт, выбор ничего не сделает; добавьте ветку 'иначе:'"),
+        );
+    }
+
+    fn check_loop(
+        &mut self,
+        condition: Option<&'a Expr>,
+        variable: Option<(&str, Inferred)>,
+        body: &'a [Stmt],
+    ) {
+        // Переменная цикла `для` видна только в теле
+        let outer = variable.and_then(|(name, _)| self.variables.get(name).cloned());
+        let bind = |variables: &mut Scope| {
+            if let Some((name, ty)) = variable {
+                variables.insert(name.to_string(), Variable { ty, annotation: None });
+            }
+        };
+
+        // Тело может выполниться много раз, поэтому сначала находим
+        // типы переменных, которые не меняются от итерации к итерации
+        loop {
+            let before = self.variables.clone();
+            bind(&mut self.variables);
+            if let Some(condition) = condition {
+                self.check_condition_silently(condition);
+            }
+            self.check_silently(body);
+            let joined = join_scopes(&before, &self.variables);
+            self.variables = joined;
+            if self.variables == before {
+                break;
+            }
+        }
+
+        let before = self.variables.clone();
+        bind(&mut self.variables);
+        if let Some(condition) = condition {
+            self.check_condition(condition);
+        }
+        self.check_scoped_block(body);
+        self.variables = join_scopes(&before, &self.variables);
+
+        if let Some((name, _)) = variable {
+            match outer {
+                Some(var) => self.variables.insert(name.to_string(), var),
+                None => self.variables.remove(name),
+            };
+        }
+    }
+
+    fn check_condition(&mut self, condition: &'a Expr) {
+        if let Some(actual) = self.check_expression(condition) {
+            if actual != Type::Булево {
+                self.mismatch(Type::Булево, actual, condition.span)
+                    .notes
+                    .push("условие должно быть истиной или ложью".to_string());
+            }
+        }
+    }
+
+    fn check_condition_silently(&mut self, condition: &'a Expr) {
+        let reported = self.diagnostics.len();
+        self.check_expression(condition);
+        self.diagnostics.truncate(reported);
+    }
+
+    fn check_expression(&mut self, expr: &'a Expr) -> Inferred {
+        match &expr.kind {
+            ExprKind::Число(_) => Some(Type::Число),
+            ExprKind::Строка(_) => Some(Type::Строка),
+            // Подставить можно значение любого типа
+            ExprKind::Шаблон(parts) => {
+                for part in parts {
+                    if let TemplatePart::Выражение(expr) = part {
+                        self.check_expression(expr);
+                    }
+                }
+                Some(Type::Строка)
+            }
+            ExprKind::Булево(_) => Some(Type::Булево),
+            ExprKind::Ошибка => None,
+            ExprKind::Этот => Some(Type::Объект),
+            ExprKind::Идентификатор(name) => match self.variables.get(name) {
+                Some(var) => var.ty,
+                None if self.functions.contains_key(name) => Some(Type::Функция),
+                None => None,
+            },
+            ExprKind::Лямбда(lambda) => {
+                let scope = self.outer_scope(&lambda.params);
+                self.check_function(None, scope, lambda.result.as_ref(), &lambda.body, expr.span);
+                Some(Type::Функция)
+            }
+            // Тип результата функции-значения неизвестен
+            ExprKind::Вызов { функция, аргументы } => {
+                let actual = self.check_expression(функция);
+                self.expect_callable(actual, функция.span);
+                for arg in аргументы {
+                    self.check_expression(arg);
+                }
+                self.forget_reassigned();
+                None
+            }
+            ExprKind::БинарнаяОперация { левый, оператор, правый } => {
+                let left = self.check_expression(левый);
+                let right = self.check_expression(правый);
+                self.check_binary(оператор, (левый, left), (правый, right), expr.span)
+            }
+            ExprKind::ВызовФункции { имя, аргументы } => {
+                let result = self.check_call(имя, аргументы, expr.span);
+                self.forget_reassigned();
+                result
+            }
+            ExprKind::Список(элементы) => {
+                for item in элементы {
+                    self.check_expression(item);
+                }
+                Some(Type::Список)
+            }
+            ExprKind::Словарь(пары) => {
+                for (key, value) in пары {
+                    let actual = self.check_expression(key);
+                    self.expect_one_of(&[Type::Число, Type::Строка], actual, key.span);
+                    self.check_expression(value);
+                }
+                Some(Type::Словарь)
+            }
+            // Тип элементов списка и значений словаря не отслеживается
+            ExprKind::Индекс { объект, индекс } => {
+                self.check_index(объект, индекс);
+                None
+            }
+            ExprKind::ПрисваиваниеПоИндексу { объект, индекс, значение } => {
+                self.check_index(объект, индекс);
+                self.check_expression(значение)
+            }
+            ExprKind::СозданиеСтруктуры { имя, поля } => {
+                let declared = self.structs.get(имя).copied().unwrap_or_default();
+                for field in поля {
+                    let actual = self.check_expression(&field.value);
+                    let annotation = declared
+                        .iter()
+                        .find(|param| param.name == field.name)
+                        .and_then(|param| param.ty.as_ref());
+                    if let Some(annotation) = annotation {
+                        self.expect_annotated((annotation.ty, annotation.span), actual, field.value.span);
+                    }
+                }
+                Some(Type::Структура)
+            }
+            // Какая именно структура лежит в переменной, не отслеживается,
+            // поэтому тип поля неизвестен
+            ExprKind::Поле { объект, .. } => {
+                self.check_structure(объект);
+                None
+            }
+            ExprKind::ПрисваиваниеПолю { объект, значение, .. } => {
+                self.check_structure(объект);
+                self.check_expression(значение)
+            }
+            // Какой метод вызовется, зависит от класса объекта, а он не отслеживается
+            ExprKind::ВызовМетода { объект, аргументы, .. } => {
+                self.check_structure(объект);
+                for arg in аргументы {
+                    self.check_expression(arg);
+                }
+                self.forget_reassigned();
+                None
+            }
+            ExprKind::Отрицание(операнд) => {
+                let actual = self.check_expression(операнд);
+                if let Some(actual) = actual.filter(|ty| *ty != Type::Булево) {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            Stage::Semantic,
+                            codes::TYPE_MISMATCH,
+                            format!("Операция 'не' не применима к типу {}", actual),
+                        )
+                        .with_span(expr.span)
+                        .with_label(операнд.span, format!("тип {}", actual)),
+                    );
+                }
+                Some(Type::Булево)
+            }
+            ExprKind::Присваивание { имя, значение } => {
+                let actual = self.check_expression(значение);
+                match self.variables.get(имя).and_then(|var| var.annotation) {
+                    Some(annotation) => self.expect_annotated(annotation, actual, значение.span),
+                    None => {
+                        self.variables
+                            .insert(имя.clone(), Variable { ty: actual, annotation: None });
+                    }
+                }
+                actual
+            }
+        }
+    }
+
+    fn check_structure(&mut self, object: &'a Expr) {
+        let actual = self.check_expression(object);
+        let allowed = [Type::Структура, Type::Объект];
+        if let Some(actual) = actual.filter(|ty| !allowed.contains(ty)) {
+            self.mismatch(one_of(&allowed), actual, object.span)
+                .notes
+                .push("поля и методы есть только у структуры и объекта".to_string());
+        }
+    }
+
+    fn check_index(&mut self, object: &'a Expr, index: &'a Expr) {
+        let container = self.check_expression(object);
+        let indexable = [Type::Список, Type::Словарь];
+        if let Some(actual) = container.filter(|ty| !indexable.contains(ty)) {
+            self.mismatch(one_of(&indexable), actual, object.span)
+                .notes
+                .push("обращаться по индексу можно только к списку или словарю".to_string());
+        }
+        let actual = self.check_expression(index);
+        match container {
+            Some(Type::Список) => self.expect_type(Type::Число, actual, index.span),
+            _ => self.expect_one_of(&[Type::Число, Type::Строка], actual, index.span),
+        }
+    }
+
+    fn check_binary(
+        &mut self,
+        op: &BinOp,
+        (left_expr, left): (&Expr, Inferred),
+        (right_expr, right): (&Expr, Inferred),
+        span: Span,
+    ) -> Inferred {
+        // Сложение определено для чисел и для строк, логические операции —
+        // для булевых значений, равенство — для всех трёх, остальное — только для чисел
+        let (allowed, result): (&[Type], fn(Type) -> Type) = match op {
+            BinOp::Плюс => (&[Type::Число, Type::Строка, Type::Список], |ty| ty),
+            BinOp::Равно | BinOp::НеРавно => {
+                (&[Type::Число, Type::Строка, Type::Булево], |_| Type::Булево)
+            }
+            BinOp::И | BinOp::Или => (&[Type::Булево], |ty| ty),
+            BinOp::Минус
+            | BinOp::Умножить
+            | BinOp::Разделить
+            | BinOp::Остаток
+            | BinOp::Степень
+            | BinOp::ЦелоеДеление => (&[Type::Число], |ty| ty),
+            _ => (&[Type::Число], |_| Type::Булево),
+        };
+
+        let bad = |ty: Inferred| ty.is_some_and(|ty| !allowed.contains(&ty));
+        if bad(left) || bad(right) || (left.is_some() && right.is_some() && left != right) {
+            let mut diagnostic = Diagnostic::error(
+                Stage::Semantic,
+                codes::TYPE_MISMATCH,
+                match (left, right) {
+                    (Some(left), Some(right)) => format!(
+                        "Операция '{}' не применима к типам {} и {}",
+                        op.symbol(), left, right
+                    ),
+                    (Some(ty), None) | (None, Some(ty)) => {
+                        format!("Операция '{}' не применима к типу {}", op.symbol(), ty)
+                    }
+                    (None, None) => unreachable!(),
+                },
+            )
+            .with_span(span);
+            for (expr, ty) in [(left_expr, left), (right_expr, right)] {
+                if let Some(ty) = ty {
+                    diagnostic = diagnostic.with_label(expr.span, format!("тип {}", ty));
+                }
+            }
+            self.diagnostics.push(diagnostic);
+            return None;
+        }
+
+        match (left.or(right), allowed) {
+            (Some(ty), _) => Some(result(ty)),
+            // Тип результата бывает известен, даже если операнды неизвестны
+            (None, [only]) => Some(result(*only)),
+            (None, _) if matches!(op, BinOp::Равно | BinOp::НеРавно) => Some(Type::Булево),
+            (None, _) => None,
+        }
+    }
+
+    fn check_call(&mut self, name: &str, args: &'a [Expr], span: Span) -> Inferred {
+        // Переменная или параметр заслоняет одноимённые функции, в том числе встроенные
+        if let Some(var) = self.variables.get(name) {
+            let actual = var.ty;
+            self.expect_callable(actual, span);
+            for arg in args {
+                self.check_expression(arg);
+            }
+            return None;
+        }
+        if let Some(function) = self.functions.get(name) {
+            let (params, result, key) = (function.params, function.result, function_key(function.span));
+            self.check_arguments(params, args);
+            return match result {
+                Some(annotation) => Some(annotation.ty),
+                None => self.infer_result(name, key),
+            };
+        }
+
+        match name {
+            "печать" => {
+                for arg in args {
+                    self.check_expression(arg);
+                }
+                return Some(Type::Пусто);
+            }
+            "график" => {
+                for (i, arg) in args.iter().enumerate() {
+                    let actual = self.check_expression(arg);
+                    let expected = if i == 0 { Type::Функция } else { Type::Число };
+                    self.expect_type(expected, actual, arg.span);
+                }
+                return Some(Type::Пусто);
+            }
+            _ => {}
+        }
+
+        if let Some((params, result)) = builtin_signature(name) {
+            for (i, arg) in args.iter().enumerate() {
+                let actual = self.check_expression(arg);
+                if let Some(allowed) = params.get(i).filter(|allowed| !allowed.is_empty()) {
+                    self.expect_one_of(allowed, actual, arg.span);
+                }
+            }
+            return result;
+        }
+
+        if let Some(fields) = self.structs.get(name).copied() {
+            self.check_arguments(fields, args);
+            return Some(Type::Структура);
+        }
+        if self.classes.contains_key(name) {
+            let params = self.constructor_params(name);
+            self.check_arguments(params, args);
+            return Some(Type::Объект);
+        }
+        // Неизвестное имя уже найдено семантической проверкой
+        for arg in args {
+            self.check_expression(arg);
+        }
+        None
+    }
+
+    // Аргументы по аннотациям параметров функции, конструктора или полей структуры
+    fn check_arguments(&mut self, params: &[Param], args: &'a [Expr]) {
+        for (i, arg) in args.iter().enumerate() {
+            let actual = self.check_expression(arg);
+            let annotation = params.get(i).and_then(|param| param.ty.as_ref());
+            if let Some(annotation) = annotation {
+                self.expect_annotated((annotation.ty, annotation.span), actual, arg.span);
+            }
+        }
+    }
+
+    fn expect_callable(&mut self, actual: Inferred, span: Span) {
+        if let Some(actual) = actual.filter(|ty| *ty != Type::Функция) {
+            self.mismatch(Type::Функция, actual, span)
+                .notes
+                .push("вызвать можно только функцию".to_string());
+        }
+    }
+
+    fn expect_type(&mut self, expected: Type, actual: Inferred, span: Span) {
+        self.expect_one_of(&[expected], actual, span);
+    }
+
+    fn expect_one_of(&mut self, expected: &[Type], actual: Inferred, span: Span) {
+        if let Some(actual) = actual.filter(|actual| !expected.contains(actual)) {
+            self.mismatch(one_of(expected), actual, span);
+        }
+    }
+
+    // Тип результата функции `name` без аннотации: общий тип всех `вернуть`
+    fn infer_result(&mut self, name: &str, key: FunctionKey) -> Inferred {
+        if let Some(result) = self.inferred_results.get(&key) {
+            return *result;
+        }
+        let (params, body) = match self.functions.get(name) {
+            Some(function) => (function.params, function.body),
+            None => return None,
+        };
+        // Рекурсивный вызов изнутри выводимой функции
+        if !self.inferring.insert(key) {
+            return None;
+        }
+
+        let scope = param_scope(params);
+        let outer_functions = self.functions.clone();
+        self.functions.retain(|name, _| !scope.contains_key(name));
+        let outer = std::mem::replace(&mut self.variables, scope);
+        let outer_result = self.expected_result.take();
+        let outer_returns = std::mem::take(&mut self.returns);
+        self.check_silently_collecting(body);
+        let returns = std::mem::replace(&mut self.returns, outer_returns);
+        self.expected_result = outer_result;
+        self.variables = outer;
+        self.functions = outer_functions;
+
+        // Без `вернуть` в конце функция возвращает пусто
+        let fallthrough = (!always_returns(body)).then_some(Some(Type::Пусто));
+        let result = returns
+            .into_iter()
+            .chain(fallthrough)
+            .reduce(join)
+            .unwrap_or(Some(Type::Пусто));
+
+        self.inferring.remove(&key);
+        self.inferred_results.insert(key, result);
+        result
+    }
+
+    // Как `check_silently`, но типы из `вернуть` остаются в `returns`
+    fn check_silently_collecting(&mut self, stmts: &'a [Stmt]) {
+        let reported = self.diagnostics.len();
+        self.check_block(stmts);
+        self.diagnostics.truncate(reported);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::lexer::Lexer;
+    use crate::compiler::parser::Parser;
+    
+    // Коды ошибок проверки типов для программы без синтаксических ошибок
+    fn errors(code: &str) -> Vec<String> {
+        let (tokens, lex_errors) = Lexer::new(code.to_string()).tokenize();
+        assert!(lex_errors.is_empty(), "лексические ошибки: {:?}", lex_errors);
+        let (program, parse_errors) = Parser::new(tokens).parse();
+        assert!(parse_errors.is_empty(), "синтаксические ошибки: {:?}", parse_errors);
+        check(&program).into_iter().map(|diagnostic| diagnostic.code).collect()
+    }
+    
+    #[test]
+    fn annotations_are_checked() {
+        assert_eq!(errors("пусть x: число = \"a\";"), [codes::TYPE_MISMATCH]);
+        assert_eq!(errors("пусть x: число = 1; x = \"a\";"), [codes::TYPE_MISMATCH]);
+        assert_eq!(errors("пусть x = \"a\"; печать(x - 1);"), [codes::TYPE_MISMATCH]);
+        assert!(errors("пусть x = 1; x = \"a\"; печать(x + \"b\");").is_empty());
+    }
+    
+    #[test]
+    fn call_forgets_types_of_variables_assigned_in_functions() {
+        assert!(errors("пусть x = \"a\"; функция f() { x = 5; } f(); печать(x - 1);").is_empty());
+        assert!(errors("пусть x = \"a\"; пусть g = фн() { x = 5; }; g(); печать(x - 1);").is_empty());
+        // Функция объявлена ниже вызова
+        assert!(errors("пусть x = \"a\"; f(); печать(x - 1); функция f() { x = 5; }").is_empty());
+        assert!(errors(
+            "пусть x = \"a\"; класс К { функция м() { x = 5; } } пусть к = К(); к.м(); печать(x - 1);"
+        )
+        .is_empty());
+    }
+    
+    #[test]
+    fn local_variables_keep_their_types() {
+        // Присваивание своей переменной функции не трогает внешнюю
+        assert_eq!(
+            errors("пусть x = \"a\"; функция f() { пусть x = 1; x = 5; } f(); печать(x - 1);"),
+            [codes::TYPE_MISMATCH]
+        );
+        assert_eq!(
+            errors("пусть x = \"a\"; функция f(x) { x = 5; } f(1); печать(x - 1);"),
+            [codes::TYPE_MISMATCH]
+        );
+        // Аннотированная переменная сохраняет тип и после вызова
+        assert_eq!(
+            errors("пусть x: строка = \"a\"; функция f() { x = \"b\"; } f(); печать(x - 1);"),
+            [codes::TYPE_MISMATCH]
+        );
+    }
+    
+    #[test]
+    fn conditions_must_be_boolean() {
+        assert_eq!(errors("пусть x = 1; если (x) { }"), [codes::TYPE_MISMATCH]);
+        assert_eq!(errors("пока (\"да\") { прервать; }"), [codes::TYPE_MISMATCH]);
+        assert!(errors("пусть x = 1; если (x > 0) { }").is_empty());
+    }
+    
+    #[test]
+    fn function_results_are_checked() {
+        assert_eq!(errors("функция f(x: число): число { если (x > 0) { вернуть x; } }"), [codes::MISSING_RETURN]);
+        assert_eq!(errors("функция f(): число { вернуть \"a\"; }"), [codes::TYPE_MISMATCH]);
+        // Тип результата без аннотации выводится из тела
+        assert_eq!(errors("функция f() { вернуть \"a\"; } печать(f() - 1);"), [codes::TYPE_MISMATCH]);
+        assert_eq!(errors("функция f(x: число) { вернуть x; } f(\"a\");"), [codes::TYPE_MISMATCH]);
+    }
+    
+    #[test]
+    fn boolean_match_must_cover_both_values() {
+        assert_eq!(errors("выбор (истина) { случай истина: печать(1); }"), [codes::NON_EXHAUSTIVE_MATCH]);
+        assert!(errors("выбор (истина) { случай истина: печать(1); случай ложь: печать(0); }").is_empty());
+    }
+    
+    #[test]
+    fn names_in_scope_shadow_functions() {
+        // Параметр заслоняет встроенную и объявленную функции
+        assert!(errors("функция g(длина) { вернуть длина(1, 2); }").is_empty());
+        assert!(errors("функция f(x: число) { вернуть x; } функция п(f) { вернуть f(\"a\"); }").is_empty());
+        // Одноимённые функции в разных блоках выводятся по отдельности
+        let code = "если (истина) { функция f() { вернуть 1; } печать(f() - 1); }
+                    иначе { функция f() { вернуть \"a\"; } печать(f() + \"b\"); }";
+        assert!(errors(code).is_empty());
+    }
+}
@@ -2,12 +2,18 @@
 
 mod compiler;
 
-use compiler::{compile_and_run, CompilationResult};
+use compiler::{compile_and_run, compile_and_run_file, CompilationResult};
 use std::fs;
+use std::path::Path;
 
 #[tauri::command]
-fn run_code(code: String) -> CompilationResult {
-    compile_and_run(code)
+fn run_code(code: String, path: Option<String>, project_dir: Option<String>) -> CompilationResult {
+    compile_and_run(code, path.as_deref().map(Path::new), project_dir.as_deref().map(Path::new))
+}
+
+#[tauri::command]
+fn run_file(path: String, project_dir: Option<String>) -> CompilationResult {
+    compile_and_run_file(Path::new(&path), project_dir.as_deref().map(Path::new))
 }
 
 #[tauri::command]
@@ -29,6 +35,7 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
             run_code,
+            run_file,
             save_file,
             load_file,
         ])
@@ -51,8 +51,8 @@ function App() {
   const containerRef = useRef<HTMLDivElement>(null);
   const [previewFile, setPreviewFile] = useState<FileSystemItem | null>(null);
   
-  const { fileTree, projectName, loading: fsLoading, openFolder, closeFolder, loadSubdirectory, readFile } = useFileSystem();
-  const { runCode, isRunning, output, errors } = useRusCompiler();
+  const { fileTree, rootPath, projectName, loading: fsLoading, openFolder, closeFolder, loadSubdirectory, readFile } = useFileSystem();
+  const { runCode, runFile, isRunning, output, errors } = useRusCompiler();
 
   const [draggedTab, setDraggedTab] = useState<number | null>(null);
   const [draggedFile, setDraggedFile] = useState<FileSystemItem | null>(null);
@@ -316,7 +316,11 @@ const handleFileDragEnd = () => {
 
   const handleRun = async () => {
     const activeFile = openFiles[activeFileIndex];
-    const result = await runCode(activeFile.content);
+    // Подключённые файлы ищутся рядом с файлом вкладки, даже если изменения ещё не сохранены
+    const untitled = activeFile.path.startsWith('untitled-');
+    const result = untitled || activeFile.modified
+      ? await runCode(activeFile.content, untitled ? null : activeFile.path, rootPath || null)
+      : await runFile(activeFile.path, rootPath || null);
     
     if (result?.plots && result.plots.length > 0) {
       setPlots([...result.plots]);
@@ -27,28 +27,50 @@ export function CodeEditor({ value, onChange, onRun }: EditorProps) {
         'фн',
         'если',
         'иначе',
+        'выбор',
+        'случай',
+        'попытка',
+        'перехват',
+        'наконец',
+        'бросить',
+        'подключить',
         'пока',
+        'для',
         'вернуть',
+        'прервать',
+        'продолжить',
+        'структура',
+        'класс',
+        'наследует',
+        'этот',
         'истина',
         'ложь',
+        'и',
+        'или',
+        'не',
         'печать',
       ],
 
+      typeKeywords: ['число', 'строка', 'булево', 'список', 'словарь', 'объект', 'пусто'],
+
       tokenizer: {
         root: [
           [/[а-яА-ЯёЁ_][а-яА-ЯёЁ0-9_]*/, {
             cases: {
               '@keywords': 'keyword',
+              '@typeKeywords': 'type',
               '@default': 'identifier',
             },
           }],
           [/[0-9]+(\.[0-9]+)?/, 'number'],
-          [/".*?"/, 'string'],
+          [/"/, 'string', '@string'],
           [/\/\/.*$/, 'comment'],
           [/\/\*/, 'comment', '@comment'],
           [/[{}()\[\]]/, '@brackets'],
-          [/[;,]/, 'delimiter'],
+          [/[;,:.]/, 'delimiter'],
           // Явный regex для всех операторов — это решает проблему
+          [/\b(div|mod)\b/, 'keyword'],
+          [/&&|\|\||\*\*|[%^]/, 'operator'],
           [/[=+\-*/><!]=?|[=!<>]=|[+\-*/><!]/, 'operator'],
           [/\s+/, 'white'],
         ],
@@ -56,6 +78,18 @@ export function CodeEditor({ value, onChange, onRun }: EditorProps) {
           [/\*\//, 'comment', '@pop'],
           [/./, 'comment'],
         ],
+        // Строка с подстановками: "x = {x}", '{{' и '}}' — сами скобки
+        string: [
+          [/\{\{|\}\}/, 'string.escape'],
+          [/\{/, 'delimiter.bracket', '@substitution'],
+          [/[^"{}]+/, 'string'],
+          [/\}/, 'string'],
+          [/"/, 'string', '@pop'],
+        ],
+        substitution: [
+          [/\}/, 'delimiter.bracket', '@pop'],
+          { include: 'root' },
+        ],
       },
     });
 
@@ -66,8 +100,10 @@ export function CodeEditor({ value, onChange, onRun }: EditorProps) {
       rules: [
         { token: 'keyword', foreground: 'C586C0', fontStyle: 'bold' },
         { token: 'identifier', foreground: '9CDCFE' },
+        { token: 'type', foreground: '4EC9B0' },
         { token: 'number', foreground: 'B5CEA8' },
         { token: 'string', foreground: 'CE9178' },
+        { token: 'string.escape', foreground: 'D7BA7D' },
         { token: 'comment', foreground: '6A9955', fontStyle: 'italic' },
         { token: 'operator', foreground: 'D4D4D4' },
       ],
@@ -106,6 +142,27 @@ export function CodeEditor({ value, onChange, onRun }: EditorProps) {
             insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
             documentation: 'Объявление функции',
           },
+          {
+            label: 'фн',
+            kind: monacoInstance.languages.CompletionItemKind.Keyword,
+            insertText: 'фн(${1:x}) {\n\t$0\n}',
+            insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+            documentation: 'Анонимная функция',
+          },
+          {
+            label: 'структура',
+            kind: monacoInstance.languages.CompletionItemKind.Keyword,
+            insertText: 'структура ${1:Имя} {\n\t${2:поле}\n}',
+            insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+            documentation: 'Объявление структуры',
+          },
+          {
+            label: 'класс',
+            kind: monacoInstance.languages.CompletionItemKind.Keyword,
+            insertText: 'класс ${1:Имя} {\n\t${2:поле} = ${3:значение};\n\n\tфункция конструктор(${4}) {\n\t\t$0\n\t}\n}',
+            insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+            documentation: 'Объявление класса',
+          },
           {
             label: 'если',
             kind: monacoInstance.languages.CompletionItemKind.Keyword,
@@ -113,6 +170,20 @@ export function CodeEditor({ value, onChange, onRun }: EditorProps) {
             insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
             documentation: 'Условный оператор',
           },
+          {
+            label: 'выбор',
+            kind: monacoInstance.languages.CompletionItemKind.Keyword,
+            insertText: 'выбор (${1:значение}) {\n\tслучай ${2:1}:\n\t\t$0\n\tиначе:\n\t\t\n}',
+            insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+            documentation: 'Выбор по значению',
+          },
+          {
+            label: 'попытка',
+            kind: monacoInstance.languages.CompletionItemKind.Keyword,
+            insertText: 'попытка {\n\t$0\n} перехват (${1:ошибка}) {\n\tпечать(${1:ошибка}.сообщение);\n}',
+            insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+            documentation: 'Перехват ошибок',
+          },
           {
             label: 'пока',
             kind: monacoInstance.languages.CompletionItemKind.Keyword,
@@ -120,6 +191,13 @@ export function CodeEditor({ value, onChange, onRun }: EditorProps) {
             insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
             documentation: 'Цикл',
           },
+          {
+            label: 'для',
+            kind: monacoInstance.languages.CompletionItemKind.Keyword,
+            insertText: 'для ${1:и} от ${2:1} до ${3:10} {\n\t$0\n}',
+            insertTextRules: monacoInstance.languages.CompletionItemInsertTextRule.InsertAsSnippet,
+            documentation: 'Цикл со счётчиком',
+          },
           {
             label: 'печать',
             kind: monacoInstance.languages.CompletionItemKind.Function,
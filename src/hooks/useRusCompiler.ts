@@ -2,11 +2,46 @@ import { invoke } from '@tauri-apps/api/core';
 import { open, save } from '@tauri-apps/plugin-dialog';
 import { useState, useCallback } from 'react';
 
+export interface Span {
+  // Номер файла в CompilationResult.files
+  file: number;
+  start: number;
+  end: number;
+  line: number;
+  column: number;
+  end_line: number;
+  end_column: number;
+}
+
+export interface StackFrame {
+  function: string;
+  span: Span;
+  arguments: string[];
+}
+
+export interface Fix {
+  message: string;
+  edits: { span: Span; replacement: string }[];
+}
+
+export interface Diagnostic {
+  severity: 'error' | 'warning' | 'note';
+  stage: 'loading' | 'lexing' | 'parsing' | 'semantic' | 'runtime';
+  code: string;
+  message: string;
+  span: Span | null;
+  labels: { span: Span; message: string }[];
+  notes: string[];
+  trace: StackFrame[];
+  fixes: Fix[];
+}
+
 interface CompilationResult {
   success: boolean;
   output: string;
-  errors: string[];
+  diagnostics: Diagnostic[];
   plots?: PlotData[];
+  files: string[];
 }
 
 interface PlotData {
@@ -15,24 +50,47 @@ interface PlotData {
   label: string;
 }
 
+// Имя файла показываем, только если программа состоит из нескольких файлов
+function formatPosition(span: Span, files: string[]): string {
+  const position = `строка ${span.line}, столбец ${span.column}`;
+  return files.length > 1 ? `${files[span.file]}, ${position}` : position;
+}
+
+export function formatDiagnostic(d: Diagnostic, files: string[] = []): string {
+  let text = `[${d.code}] ${d.message}`;
+  if (d.span) {
+    text += ` (${formatPosition(d.span, files)})`;
+  }
+  for (const frame of d.trace) {
+    text += `\n  в вызове ${frame.function}(${frame.arguments.join(', ')}) (${formatPosition(frame.span, files)})`;
+  }
+  for (const note of d.notes) {
+    text += `\nподсказка: ${note}`;
+  }
+  return text;
+}
+
 export function useRusCompiler() {
   const [isRunning, setIsRunning] = useState(false);
   const [output, setOutput] = useState('');
   const [errors, setErrors] = useState<string[]>([]);
+  const [diagnostics, setDiagnostics] = useState<Diagnostic[]>([]);
   const [plots, setPlots] = useState<PlotData[]>([]);
   const [currentFilePath, setCurrentFilePath] = useState<string | null>(null);
 
-  const runCode = useCallback(async (code: string) => {
+  const run = useCallback(async (command: string, args: Record<string, unknown>) => {
     setIsRunning(true);
     setOutput('');
     setErrors([]);
+    setDiagnostics([]);
     setPlots([]);
 
     try {
-      const result = await invoke<CompilationResult>('run_code', { code });
+      const result = await invoke<CompilationResult>(command, args);
       
       setOutput(result.output);
-      setErrors(result.errors);
+      setDiagnostics(result.diagnostics);
+      setErrors(result.diagnostics.map(d => formatDiagnostic(d, result.files)));
       
       if (result.plots) {
         setPlots(result.plots);
@@ -47,6 +105,16 @@ export function useRusCompiler() {
     }
   }, []);
 
+  // Код из редактора; `path` — файл вкладки, если он есть: от его папки считаются пути в `подключить`
+  const runCode = useCallback(async (code: string, path?: string | null, projectDir?: string | null) => {
+    return await run('run_code', { code, path: path ?? null, projectDir: projectDir ?? null });
+  }, [run]);
+
+  // Файл с диска: пути в `подключить` считаются от его папки
+  const runFile = useCallback(async (path: string, projectDir?: string | null) => {
+    return await run('run_file', { path, projectDir: projectDir ?? null });
+  }, [run]);
+
   const saveFile = useCallback(async (content: string, path?: string) => {
     try {
       let filePath = path || currentFilePath;
@@ -113,6 +181,7 @@ export function useRusCompiler() {
 
   return {
     runCode,
+    runFile,
     saveFile,
     saveFileAs,
     openFile,
@@ -120,6 +189,7 @@ export function useRusCompiler() {
     isRunning,
     output,
     errors,
+    diagnostics,
     plots,
     currentFilePath,
   };